@@ -1,8 +1,11 @@
+use crate::endpoint::Endpoint;
 use crate::errors::{Error, Kind};
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
 use serde::*;
 
-trait Accounts {
+pub trait Accounts {
     fn get_accounts(&self, access_token: &str) -> Result<GetAccountsResponse, Error>;
     fn get_accounts_with_options(
         &self,
@@ -69,21 +72,36 @@ pub struct BACSNumber {
     pub sort_code: String,
 }
 
-#[derive(Serialize)]
-struct GetAccountsRequestOptions<'a> {
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GetAccountsRequestOptions<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     account_ids: Option<Vec<&'a str>>,
 }
 
+impl<'a> GetAccountsRequestOptions<'a> {
+    pub fn new() -> GetAccountsRequestOptions<'a> {
+        GetAccountsRequestOptions::default()
+    }
+
+    /// Only returns data for the accounts with these IDs.
+    pub fn with_account_ids(mut self, account_ids: Vec<&'a str>) -> GetAccountsRequestOptions<'a> {
+        self.account_ids = Some(account_ids);
+        self
+    }
+}
+
 #[derive(Serialize)]
 struct GetAccountsRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<GetAccountsRequestOptions<'a>>,
 }
 
+impl Endpoint for GetAccountsRequest<'_> {
+    type Response = GetAccountsResponse;
+    const PATH: &'static str = "/accounts/get";
+}
+
 #[derive(Deserialize)]
 pub struct GetAccountsResponse {
     pub request_id: String,
@@ -92,26 +110,41 @@ pub struct GetAccountsResponse {
 
 #[derive(Serialize)]
 struct GetBalancesRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<GetBalancesRequestOptions<'a>>,
 }
 
-#[derive(Serialize)]
-struct GetBalancesRequestOptions<'a> {
+impl Endpoint for GetBalancesRequest<'_> {
+    type Response = GetBalancesResponse;
+    const PATH: &'static str = "/accounts/balance/get";
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GetBalancesRequestOptions<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     account_ids: Option<Vec<&'a str>>,
 }
 
+impl<'a> GetBalancesRequestOptions<'a> {
+    pub fn new() -> GetBalancesRequestOptions<'a> {
+        GetBalancesRequestOptions::default()
+    }
+
+    /// Only returns data for the accounts with these IDs.
+    pub fn with_account_ids(mut self, account_ids: Vec<&'a str>) -> GetBalancesRequestOptions<'a> {
+        self.account_ids = Some(account_ids);
+        self
+    }
+}
+
 #[derive(Deserialize)]
 pub struct GetBalancesResponse {
     pub request_id: String,
     pub accounts: Vec<Account>,
 }
 
-impl<'a> Accounts for Client<'a> {
+impl Accounts for Client {
     fn get_accounts(&self, access_token: &str) -> Result<GetAccountsResponse, Error> {
         self.get_accounts_with_options(access_token, None)
     }
@@ -121,20 +154,16 @@ impl<'a> Accounts for Client<'a> {
         access_token: &str,
         options: Option<GetAccountsRequestOptions>,
     ) -> Result<GetAccountsResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
         let req = GetAccountsRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/accounts/get", &json_body))
+        self.send(&req)
     }
 
     fn get_balances(&self, access_token: &str) -> Result<GetBalancesResponse, Error> {
@@ -146,20 +175,136 @@ impl<'a> Accounts for Client<'a> {
         access_token: &str,
         options: Option<GetBalancesRequestOptions>,
     ) -> Result<GetBalancesResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
         let req = GetBalancesRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/accounts/balance/get", &json_body))
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncAccounts {
+    fn get_accounts(&self, access_token: &str) -> ResponseFuture<GetAccountsResponse>;
+    fn get_accounts_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetAccountsRequestOptions>,
+    ) -> ResponseFuture<GetAccountsResponse>;
+    fn get_balances(&self, access_token: &str) -> ResponseFuture<GetBalancesResponse>;
+    fn get_balances_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetBalancesRequestOptions>,
+    ) -> ResponseFuture<GetBalancesResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncAccounts for AsyncClient {
+    fn get_accounts(&self, access_token: &str) -> ResponseFuture<GetAccountsResponse> {
+        self.get_accounts_with_options(access_token, None)
+    }
+
+    fn get_accounts_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetAccountsRequestOptions>,
+    ) -> ResponseFuture<GetAccountsResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetAccountsRequest {
+            access_token,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn get_balances(&self, access_token: &str) -> ResponseFuture<GetBalancesResponse> {
+        self.get_balances_with_options(access_token, None)
+    }
+
+    fn get_balances_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetBalancesRequestOptions>,
+    ) -> ResponseFuture<GetBalancesResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetBalancesRequest {
+            access_token,
+            options,
+        };
+
+        self.send(&req)
+    }
+}
+
+/// AccountsApi groups the accounts and balances endpoints, e.g.
+/// `client.accounts().get(..)`.
+pub struct AccountsApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl AccountsApi<'_, Client> {
+    pub fn get(&self, access_token: &str) -> Result<GetAccountsResponse, Error> {
+        self.client.get_accounts(access_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetAccountsRequestOptions>,
+    ) -> Result<GetAccountsResponse, Error> {
+        self.client.get_accounts_with_options(access_token, options)
+    }
+
+    pub fn get_balances(&self, access_token: &str) -> Result<GetBalancesResponse, Error> {
+        self.client.get_balances(access_token)
+    }
+
+    pub fn get_balances_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetBalancesRequestOptions>,
+    ) -> Result<GetBalancesResponse, Error> {
+        self.client.get_balances_with_options(access_token, options)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AccountsApi<'_, AsyncClient> {
+    pub fn get(&self, access_token: &str) -> ResponseFuture<GetAccountsResponse> {
+        self.client.get_accounts(access_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetAccountsRequestOptions>,
+    ) -> ResponseFuture<GetAccountsResponse> {
+        self.client.get_accounts_with_options(access_token, options)
+    }
+
+    pub fn get_balances(&self, access_token: &str) -> ResponseFuture<GetBalancesResponse> {
+        self.client.get_balances(access_token)
+    }
+
+    pub fn get_balances_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetBalancesRequestOptions>,
+    ) -> ResponseFuture<GetBalancesResponse> {
+        self.client.get_balances_with_options(access_token, options)
     }
 }
 
@@ -169,20 +314,29 @@ mod tests {
     use crate::environments::Environment;
     use crate::items::Items;
     use crate::sandbox::Sandbox;
-    use std::env;
+    use crate::transport::InMemoryTransport;
 
     #[test]
     fn test_get_accounts() {
-        let client_id = env::var("PLAID_CLIENT_ID").unwrap();
-        let secret = env::var("PLAID_SECRET").unwrap();
-        let public_key = env::var("PLAID_PUBLIC_KEY").unwrap();
-        let test_client = Client {
-            client_id: client_id.as_str(),
-            secret: secret.as_str(),
-            public_key: public_key.as_str(),
-            environment: Environment::SANDBOX,
-            http_client: reqwest::Client::new(),
-        };
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/sandbox/public_token/create",
+                include_str!("../fixtures/sandbox/public_token/create.json"),
+            )
+            .with_response(
+                "/item/public_token/exchange",
+                include_str!("../fixtures/item/public_token/exchange.json"),
+            )
+            .with_response(
+                "/accounts/get",
+                include_str!("../fixtures/accounts/get.json"),
+            )
+            .with_response(
+                "/accounts/get",
+                include_str!("../fixtures/accounts/get_filtered.json"),
+            );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone());
 
         let sandbox_resp = test_client
             .create_sandbox_public_token(
@@ -210,20 +364,36 @@ mod tests {
             .unwrap();
 
         assert_eq!(1, filtered_accounts_resp.accounts.len());
+
+        let requests = transport.requests_to("/accounts/get");
+        assert_eq!(2, requests.len());
+        assert!(!requests[0].body.contains("options"));
+        assert!(requests[1]
+            .body
+            .contains(r#""options":{"account_ids":["BxBXxLj1m4HMXBm9WZZmCWVbPjX16EHwv99vp"]}"#));
     }
 
     #[test]
     fn test_get_balances() {
-        let client_id = env::var("PLAID_CLIENT_ID").unwrap();
-        let secret = env::var("PLAID_SECRET").unwrap();
-        let public_key = env::var("PLAID_PUBLIC_KEY").unwrap();
-        let test_client = Client {
-            client_id: client_id.as_str(),
-            secret: secret.as_str(),
-            public_key: public_key.as_str(),
-            environment: Environment::SANDBOX,
-            http_client: reqwest::Client::new(),
-        };
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/sandbox/public_token/create",
+                include_str!("../fixtures/sandbox/public_token/create.json"),
+            )
+            .with_response(
+                "/item/public_token/exchange",
+                include_str!("../fixtures/item/public_token/exchange.json"),
+            )
+            .with_response(
+                "/accounts/balance/get",
+                include_str!("../fixtures/accounts/balance/get.json"),
+            )
+            .with_response(
+                "/accounts/balance/get",
+                include_str!("../fixtures/accounts/balance/get_filtered.json"),
+            );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport);
 
         let sandbox_resp = test_client
             .create_sandbox_public_token(
@@ -252,4 +422,58 @@ mod tests {
 
         assert_eq!(1, filtered_balances_resp.accounts.len());
     }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_get_accounts_async() {
+        use crate::items::AsyncItems;
+        use crate::plaid::AsyncClient;
+        use crate::sandbox::AsyncSandbox;
+        use futures::Future;
+
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/sandbox/public_token/create",
+                include_str!("../fixtures/sandbox/public_token/create.json"),
+            )
+            .with_response(
+                "/item/public_token/exchange",
+                include_str!("../fixtures/item/public_token/exchange.json"),
+            )
+            .with_response(
+                "/accounts/get",
+                include_str!("../fixtures/accounts/get.json"),
+            )
+            .with_response(
+                "/accounts/balance/get",
+                include_str!("../fixtures/accounts/balance/get.json"),
+            );
+        let test_client =
+            AsyncClient::new("client_id", "secret", "public_key", Environment::SANDBOX)
+                .with_transport(transport);
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+
+        let sandbox_resp = runtime
+            .block_on(test_client.create_sandbox_public_token(
+                "ins_109508",
+                &["auth", "identity", "income", "transactions"],
+            ))
+            .unwrap();
+        let token_resp = runtime
+            .block_on(test_client.exchange_public_token(sandbox_resp.public_token.as_str()))
+            .unwrap();
+
+        let access_token = token_resp.access_token.as_str();
+        let accounts_resp = runtime
+            .block_on(
+                test_client
+                    .get_accounts(access_token)
+                    .join(test_client.get_balances(access_token)),
+            )
+            .unwrap();
+
+        assert_eq!(8, accounts_resp.0.accounts.len());
+        assert_eq!(8, accounts_resp.1.accounts.len());
+    }
 }
@@ -1,15 +1,51 @@
 use crate::accounts::Account;
+use crate::endpoint::{Endpoint, RawEndpoint};
 use crate::errors::{Error, Kind};
+use crate::identities::Identity;
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
 use serde::*;
+use std::io::Write;
+use std::sync::{Arc, Mutex};
 
-trait Assets {
+pub trait Assets {
+    fn create_asset_report(
+        &self,
+        access_tokens: &[&str],
+        days_requested: i64,
+        options: Option<AssetReportRequestOptions>,
+    ) -> Result<CreateAssetReportResponse, Error>;
+    fn refresh_asset_report(
+        &self,
+        asset_report_token: &str,
+        days_requested: Option<i64>,
+        options: Option<AssetReportRequestOptions>,
+    ) -> Result<RefreshAssetReportResponse, Error>;
+    fn filter_asset_report(
+        &self,
+        asset_report_token: &str,
+        account_ids_to_exclude: &[&str],
+    ) -> Result<FilterAssetReportResponse, Error>;
     fn get_asset_report(&self, asset_report_token: &str) -> Result<GetAssetReportResponse, Error>;
+    fn get_asset_report_with_options(
+        &self,
+        asset_report_token: &str,
+        options: Option<GetAssetReportRequestOptions>,
+    ) -> Result<GetAssetReportResponse, Error>;
+    fn get_asset_report_pdf(&self, asset_report_token: &str) -> Result<Vec<u8>, Error>;
+    fn write_asset_report_pdf(
+        &self,
+        asset_report_token: &str,
+        writer: &mut dyn Write,
+    ) -> Result<u64, Error>;
     fn create_audit_copy(
         &self,
         asset_report_token: &str,
         auditor_id: &str,
     ) -> Result<CreateAuditCopyTokenResponse, Error>;
+    fn get_audit_copy(&self, audit_copy_token: &str) -> Result<GetAssetReportResponse, Error>;
+    fn remove_audit_copy(&self, audit_copy_token: &str) -> Result<RemoveAuditCopyResponse, Error>;
     fn remove_asset_report(
         &self,
         asset_report_token: &str,
@@ -19,7 +55,7 @@ trait Assets {
 #[derive(Deserialize)]
 pub struct AssetReport {
     pub asset_report_id: String,
-    pub client_report_id: String,
+    pub client_report_id: Option<String>,
     pub date_generated: String,
     pub days_requested: i64,
     pub items: Vec<AssetReportItem>,
@@ -28,29 +64,212 @@ pub struct AssetReport {
 
 #[derive(Deserialize)]
 pub struct AssetReportItem {
-    pub accounts: Vec<Account>,
+    pub accounts: Vec<AssetReportAccount>,
     pub date_last_updated: String,
     pub institution_id: String,
     pub institution_name: String,
     pub item_id: String,
 }
 
+/// AssetReportAccount is an account as it appears in an asset report, with
+/// the balance history, transactions and owners the report covers.
+#[derive(Deserialize)]
+pub struct AssetReportAccount {
+    /// How many days of history the institution had for the account.
+    pub days_available: f64,
+    pub historical_balances: Vec<HistoricalBalance>,
+    pub owners: Vec<Identity>,
+    pub transactions: Vec<AssetReportTransaction>,
+    #[serde(flatten)]
+    pub account: Account,
+}
+
+/// HistoricalBalance is the balance of an account at the end of a day.
+#[derive(Deserialize)]
+pub struct HistoricalBalance {
+    pub current: f64,
+    pub date: String,
+    pub iso_currency_code: Option<String>,
+    pub unofficial_currency_code: Option<String>,
+}
+
+/// AssetReportTransaction is a transaction as it appears in an asset report.
+/// The name, category and merchant fields are only filled in for reports
+/// fetched with insights.
 #[derive(Deserialize)]
+pub struct AssetReportTransaction {
+    pub account_id: String,
+    pub amount: f64,
+    pub date: String,
+    pub iso_currency_code: Option<String>,
+    pub unofficial_currency_code: Option<String>,
+    pub original_description: Option<String>,
+    pub pending: bool,
+    pub transaction_id: String,
+    #[serde(default)]
+    pub account_owner: Option<String>,
+    #[serde(default)]
+    pub category: Option<Vec<String>>,
+    #[serde(default)]
+    pub category_id: Option<String>,
+    #[serde(default)]
+    pub date_transacted: Option<String>,
+    #[serde(default)]
+    pub merchant_name: Option<String>,
+    #[serde(default)]
+    pub name: Option<String>,
+}
+
+/// AssetReportUser identifies the borrower a report is about. It is returned
+/// in every report, and can be given when a report is created or refreshed,
+/// in which case `None` fields are left out of the request. Fields that were
+/// not given then come back as `None`.
+#[derive(Clone, Debug, Default, Deserialize, Serialize)]
 pub struct AssetReportUser {
-    pub client_user_id: String,
-    pub email: String,
-    pub first_name: String,
-    pub last_name: String,
-    pub middle_name: String,
-    pub phone_number: String,
-    pub ssn: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub client_user_id: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub email: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub first_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub last_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub middle_name: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub phone_number: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub ssn: Option<String>,
+}
+
+/// The most days of history an asset report can cover.
+pub const MAX_DAYS_REQUESTED: i64 = 730;
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct AssetReportRequestOptions<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_report_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    webhook: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    user: Option<AssetReportUser>,
+}
+
+impl<'a> AssetReportRequestOptions<'a> {
+    pub fn new() -> AssetReportRequestOptions<'a> {
+        AssetReportRequestOptions::default()
+    }
+
+    /// Tags the report with an ID of your own, returned in the report.
+    pub fn with_client_report_id(
+        mut self,
+        client_report_id: &'a str,
+    ) -> AssetReportRequestOptions<'a> {
+        self.client_report_id = Some(client_report_id);
+        self
+    }
+
+    /// Has Plaid notify `webhook` once the report is ready.
+    pub fn with_webhook(mut self, webhook: &'a str) -> AssetReportRequestOptions<'a> {
+        self.webhook = Some(webhook);
+        self
+    }
+
+    /// Includes details of the borrower in the report.
+    pub fn with_user(mut self, user: AssetReportUser) -> AssetReportRequestOptions<'a> {
+        self.user = Some(user);
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct CreateAssetReportRequest<'a> {
+    access_tokens: &'a [&'a str],
+    days_requested: i64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<AssetReportRequestOptions<'a>>,
+}
+
+impl Endpoint for CreateAssetReportRequest<'_> {
+    type Response = CreateAssetReportResponse;
+    const PATH: &'static str = "/asset_report/create";
+}
+
+#[derive(Deserialize)]
+pub struct CreateAssetReportResponse {
+    pub request_id: String,
+    pub asset_report_token: String,
+    pub asset_report_id: String,
+}
+
+#[derive(Serialize)]
+struct RefreshAssetReportRequest<'a> {
+    asset_report_token: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    days_requested: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    options: Option<AssetReportRequestOptions<'a>>,
+}
+
+impl Endpoint for RefreshAssetReportRequest<'_> {
+    type Response = RefreshAssetReportResponse;
+    const PATH: &'static str = "/asset_report/refresh";
+}
+
+#[derive(Deserialize)]
+pub struct RefreshAssetReportResponse {
+    pub request_id: String,
+    pub asset_report_token: String,
+    pub asset_report_id: String,
+}
+
+#[derive(Serialize)]
+struct FilterAssetReportRequest<'a> {
+    asset_report_token: &'a str,
+    account_ids_to_exclude: &'a [&'a str],
+}
+
+impl Endpoint for FilterAssetReportRequest<'_> {
+    type Response = FilterAssetReportResponse;
+    const PATH: &'static str = "/asset_report/filter";
+}
+
+#[derive(Deserialize)]
+pub struct FilterAssetReportResponse {
+    pub request_id: String,
+    pub asset_report_token: String,
+    pub asset_report_id: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GetAssetReportRequestOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    include_insights: Option<bool>,
+}
+
+impl GetAssetReportRequestOptions {
+    pub fn new() -> GetAssetReportRequestOptions {
+        GetAssetReportRequestOptions::default()
+    }
+
+    /// Returns the report with insights: cleaned up names, categories and
+    /// merchants for every transaction.
+    pub fn with_include_insights(mut self, include_insights: bool) -> GetAssetReportRequestOptions {
+        self.include_insights = Some(include_insights);
+        self
+    }
 }
 
 #[derive(Serialize)]
 struct GetAssetReportRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     asset_report_token: &'a str,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    options: Option<GetAssetReportRequestOptions>,
+}
+
+impl Endpoint for GetAssetReportRequest<'_> {
+    type Response = GetAssetReportResponse;
+    const PATH: &'static str = "/asset_report/get";
 }
 
 #[derive(Deserialize)]
@@ -60,48 +279,185 @@ pub struct GetAssetReportResponse {
     pub warnings: Vec<String>,
 }
 
+#[derive(Serialize)]
+struct GetAssetReportPdfRequest<'a> {
+    asset_report_token: &'a str,
+}
+
+impl RawEndpoint for GetAssetReportPdfRequest<'_> {
+    const PATH: &'static str = "/asset_report/pdf/get";
+}
+
 #[derive(Serialize)]
 struct CreateAuditCopyRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     asset_report_token: &'a str,
     auditor_id: &'a str,
 }
 
+impl Endpoint for CreateAuditCopyRequest<'_> {
+    type Response = CreateAuditCopyTokenResponse;
+    const PATH: &'static str = "/asset_report/audit_copy/create";
+}
+
 #[derive(Deserialize)]
 pub struct CreateAuditCopyTokenResponse {
     pub request_id: String,
     pub audit_copy_token: String,
 }
 
+#[derive(Serialize)]
+struct GetAuditCopyRequest<'a> {
+    audit_copy_token: &'a str,
+}
+
+impl Endpoint for GetAuditCopyRequest<'_> {
+    type Response = GetAssetReportResponse;
+    const PATH: &'static str = "/asset_report/audit_copy/get";
+}
+
+#[derive(Serialize)]
+struct RemoveAuditCopyRequest<'a> {
+    audit_copy_token: &'a str,
+}
+
+impl Endpoint for RemoveAuditCopyRequest<'_> {
+    type Response = RemoveAuditCopyResponse;
+    const PATH: &'static str = "/asset_report/audit_copy/remove";
+}
+
+#[derive(Deserialize)]
+pub struct RemoveAuditCopyResponse {
+    pub request_id: String,
+    pub removed: bool,
+}
+
 #[derive(Serialize)]
 struct RemoveAssetReportRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     asset_report_token: &'a str,
 }
 
+impl Endpoint for RemoveAssetReportRequest<'_> {
+    type Response = RemoveAssetReportResponse;
+    const PATH: &'static str = "/asset_report/remove";
+}
+
 #[derive(Deserialize)]
 pub struct RemoveAssetReportResponse {
     pub request_id: String,
     pub removed: bool,
 }
 
-impl<'a> Assets for Client<'a> {
+impl Assets for Client {
+    fn create_asset_report(
+        &self,
+        access_tokens: &[&str],
+        days_requested: i64,
+        options: Option<AssetReportRequestOptions>,
+    ) -> Result<CreateAssetReportResponse, Error> {
+        if access_tokens.is_empty() || access_tokens.iter().any(|token| token.is_empty()) {
+            Err(Error::new(Kind::ValidationError(
+                "at least one access token must be specified",
+            )))?
+        }
+        if !valid_days_requested(days_requested) {
+            Err(Error::new(Kind::ValidationError(
+                "days requested must be between 0 and 730",
+            )))?
+        }
+
+        let req = CreateAssetReportRequest {
+            access_tokens,
+            days_requested,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn refresh_asset_report(
+        &self,
+        asset_report_token: &str,
+        days_requested: Option<i64>,
+        options: Option<AssetReportRequestOptions>,
+    ) -> Result<RefreshAssetReportResponse, Error> {
+        if asset_report_token.is_empty() {
+            Err(Error::new(Kind::EmptyToken))?
+        }
+        if !days_requested.map_or(true, valid_days_requested) {
+            Err(Error::new(Kind::ValidationError(
+                "days requested must be between 0 and 730",
+            )))?
+        }
+
+        let req = RefreshAssetReportRequest {
+            asset_report_token,
+            days_requested,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn filter_asset_report(
+        &self,
+        asset_report_token: &str,
+        account_ids_to_exclude: &[&str],
+    ) -> Result<FilterAssetReportResponse, Error> {
+        if asset_report_token.is_empty() {
+            Err(Error::new(Kind::EmptyToken))?
+        }
+
+        let req = FilterAssetReportRequest {
+            asset_report_token,
+            account_ids_to_exclude,
+        };
+
+        self.send(&req)
+    }
+
     fn get_asset_report(&self, asset_report_token: &str) -> Result<GetAssetReportResponse, Error> {
-        if asset_report_token == "" {
+        self.get_asset_report_with_options(asset_report_token, None)
+    }
+
+    fn get_asset_report_with_options(
+        &self,
+        asset_report_token: &str,
+        options: Option<GetAssetReportRequestOptions>,
+    ) -> Result<GetAssetReportResponse, Error> {
+        if asset_report_token.is_empty() {
             Err(Error::new(Kind::EmptyToken))?
         }
 
         let req = GetAssetReportRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             asset_report_token,
+            options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/asset_report/get", &json_body))
+        self.send(&req)
+    }
+
+    fn get_asset_report_pdf(&self, asset_report_token: &str) -> Result<Vec<u8>, Error> {
+        if asset_report_token.is_empty() {
+            Err(Error::new(Kind::EmptyToken))?
+        }
+
+        let req = GetAssetReportPdfRequest { asset_report_token };
+
+        self.send_raw(&req)
+    }
+
+    fn write_asset_report_pdf(
+        &self,
+        asset_report_token: &str,
+        writer: &mut dyn Write,
+    ) -> Result<u64, Error> {
+        if asset_report_token.is_empty() {
+            Err(Error::new(Kind::EmptyToken))?
+        }
+
+        let req = GetAssetReportPdfRequest { asset_report_token };
+
+        self.write_raw(&req, writer)
     }
 
     fn create_audit_copy(
@@ -109,40 +465,875 @@ impl<'a> Assets for Client<'a> {
         asset_report_token: &str,
         auditor_id: &str,
     ) -> Result<CreateAuditCopyTokenResponse, Error> {
-        if asset_report_token == "" || auditor_id == "" {
+        if asset_report_token.is_empty() || auditor_id.is_empty() {
             Err(Error::new(Kind::ValidationError(
                 "asset report token and auditor id must be specified",
             )))?
         }
 
         let req = CreateAuditCopyRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             asset_report_token,
             auditor_id,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/asset_report/audit_copy/create", &json_body))
+        self.send(&req)
+    }
+
+    fn get_audit_copy(&self, audit_copy_token: &str) -> Result<GetAssetReportResponse, Error> {
+        if audit_copy_token.is_empty() {
+            Err(Error::new(Kind::EmptyToken))?
+        }
+
+        let req = GetAuditCopyRequest { audit_copy_token };
+
+        self.send(&req)
+    }
+
+    fn remove_audit_copy(&self, audit_copy_token: &str) -> Result<RemoveAuditCopyResponse, Error> {
+        if audit_copy_token.is_empty() {
+            Err(Error::new(Kind::EmptyToken))?
+        }
+
+        let req = RemoveAuditCopyRequest { audit_copy_token };
+
+        self.send(&req)
     }
 
     fn remove_asset_report(
         &self,
         asset_report_token: &str,
     ) -> Result<RemoveAssetReportResponse, Error> {
-        if asset_report_token == "" {
+        if asset_report_token.is_empty() {
             Err(Error::new(Kind::EmptyToken))?
         }
 
-        let req = RemoveAssetReportRequest {
-            client_id: self.client_id,
-            secret: self.secret,
+        let req = RemoveAssetReportRequest { asset_report_token };
+
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncAssets {
+    fn create_asset_report(
+        &self,
+        access_tokens: &[&str],
+        days_requested: i64,
+        options: Option<AssetReportRequestOptions>,
+    ) -> ResponseFuture<CreateAssetReportResponse>;
+    fn refresh_asset_report(
+        &self,
+        asset_report_token: &str,
+        days_requested: Option<i64>,
+        options: Option<AssetReportRequestOptions>,
+    ) -> ResponseFuture<RefreshAssetReportResponse>;
+    fn filter_asset_report(
+        &self,
+        asset_report_token: &str,
+        account_ids_to_exclude: &[&str],
+    ) -> ResponseFuture<FilterAssetReportResponse>;
+    fn get_asset_report(&self, asset_report_token: &str) -> ResponseFuture<GetAssetReportResponse>;
+    fn get_asset_report_with_options(
+        &self,
+        asset_report_token: &str,
+        options: Option<GetAssetReportRequestOptions>,
+    ) -> ResponseFuture<GetAssetReportResponse>;
+    fn get_asset_report_pdf(&self, asset_report_token: &str) -> ResponseFuture<Vec<u8>>;
+    fn create_audit_copy(
+        &self,
+        asset_report_token: &str,
+        auditor_id: &str,
+    ) -> ResponseFuture<CreateAuditCopyTokenResponse>;
+    fn get_audit_copy(&self, audit_copy_token: &str) -> ResponseFuture<GetAssetReportResponse>;
+    fn remove_audit_copy(&self, audit_copy_token: &str) -> ResponseFuture<RemoveAuditCopyResponse>;
+    fn remove_asset_report(
+        &self,
+        asset_report_token: &str,
+    ) -> ResponseFuture<RemoveAssetReportResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncAssets for AsyncClient {
+    fn create_asset_report(
+        &self,
+        access_tokens: &[&str],
+        days_requested: i64,
+        options: Option<AssetReportRequestOptions>,
+    ) -> ResponseFuture<CreateAssetReportResponse> {
+        if access_tokens.is_empty() || access_tokens.iter().any(|token| token.is_empty()) {
+            return fail(Kind::ValidationError(
+                "at least one access token must be specified",
+            ));
+        }
+        if !valid_days_requested(days_requested) {
+            return fail(Kind::ValidationError(
+                "days requested must be between 0 and 730",
+            ));
+        }
+
+        let req = CreateAssetReportRequest {
+            access_tokens,
+            days_requested,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn refresh_asset_report(
+        &self,
+        asset_report_token: &str,
+        days_requested: Option<i64>,
+        options: Option<AssetReportRequestOptions>,
+    ) -> ResponseFuture<RefreshAssetReportResponse> {
+        if asset_report_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+        if !days_requested.map_or(true, valid_days_requested) {
+            return fail(Kind::ValidationError(
+                "days requested must be between 0 and 730",
+            ));
+        }
+
+        let req = RefreshAssetReportRequest {
+            asset_report_token,
+            days_requested,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn filter_asset_report(
+        &self,
+        asset_report_token: &str,
+        account_ids_to_exclude: &[&str],
+    ) -> ResponseFuture<FilterAssetReportResponse> {
+        if asset_report_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = FilterAssetReportRequest {
             asset_report_token,
+            account_ids_to_exclude,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/asset_report/remove", &json_body))
+        self.send(&req)
+    }
+
+    fn get_asset_report(&self, asset_report_token: &str) -> ResponseFuture<GetAssetReportResponse> {
+        self.get_asset_report_with_options(asset_report_token, None)
+    }
+
+    fn get_asset_report_with_options(
+        &self,
+        asset_report_token: &str,
+        options: Option<GetAssetReportRequestOptions>,
+    ) -> ResponseFuture<GetAssetReportResponse> {
+        if asset_report_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetAssetReportRequest {
+            asset_report_token,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn get_asset_report_pdf(&self, asset_report_token: &str) -> ResponseFuture<Vec<u8>> {
+        if asset_report_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetAssetReportPdfRequest { asset_report_token };
+
+        self.send_raw(&req)
+    }
+
+    fn create_audit_copy(
+        &self,
+        asset_report_token: &str,
+        auditor_id: &str,
+    ) -> ResponseFuture<CreateAuditCopyTokenResponse> {
+        if asset_report_token.is_empty() || auditor_id.is_empty() {
+            return fail(Kind::ValidationError(
+                "asset report token and auditor id must be specified",
+            ));
+        }
+
+        let req = CreateAuditCopyRequest {
+            asset_report_token,
+            auditor_id,
+        };
+
+        self.send(&req)
+    }
+
+    fn get_audit_copy(&self, audit_copy_token: &str) -> ResponseFuture<GetAssetReportResponse> {
+        if audit_copy_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetAuditCopyRequest { audit_copy_token };
+
+        self.send(&req)
+    }
+
+    fn remove_audit_copy(&self, audit_copy_token: &str) -> ResponseFuture<RemoveAuditCopyResponse> {
+        if audit_copy_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = RemoveAuditCopyRequest { audit_copy_token };
+
+        self.send(&req)
+    }
+
+    fn remove_asset_report(
+        &self,
+        asset_report_token: &str,
+    ) -> ResponseFuture<RemoveAssetReportResponse> {
+        if asset_report_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = RemoveAssetReportRequest { asset_report_token };
+
+        self.send(&req)
+    }
+}
+
+fn valid_days_requested(days_requested: i64) -> bool {
+    (0..=MAX_DAYS_REQUESTED).contains(&days_requested)
+}
+
+/// AssetsApi groups the asset report endpoints, e.g.
+/// `client.assets().get(..)`.
+pub struct AssetsApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl AssetsApi<'_, Client> {
+    pub fn create(
+        &self,
+        access_tokens: &[&str],
+        days_requested: i64,
+        options: Option<AssetReportRequestOptions>,
+    ) -> Result<CreateAssetReportResponse, Error> {
+        self.client
+            .create_asset_report(access_tokens, days_requested, options)
+    }
+
+    pub fn refresh(
+        &self,
+        asset_report_token: &str,
+        days_requested: Option<i64>,
+        options: Option<AssetReportRequestOptions>,
+    ) -> Result<RefreshAssetReportResponse, Error> {
+        self.client
+            .refresh_asset_report(asset_report_token, days_requested, options)
+    }
+
+    pub fn filter(
+        &self,
+        asset_report_token: &str,
+        account_ids_to_exclude: &[&str],
+    ) -> Result<FilterAssetReportResponse, Error> {
+        self.client
+            .filter_asset_report(asset_report_token, account_ids_to_exclude)
+    }
+
+    pub fn get(&self, asset_report_token: &str) -> Result<GetAssetReportResponse, Error> {
+        self.client.get_asset_report(asset_report_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        asset_report_token: &str,
+        options: Option<GetAssetReportRequestOptions>,
+    ) -> Result<GetAssetReportResponse, Error> {
+        self.client
+            .get_asset_report_with_options(asset_report_token, options)
+    }
+
+    pub fn get_pdf(&self, asset_report_token: &str) -> Result<Vec<u8>, Error> {
+        self.client.get_asset_report_pdf(asset_report_token)
+    }
+
+    pub fn write_pdf(
+        &self,
+        asset_report_token: &str,
+        writer: &mut dyn Write,
+    ) -> Result<u64, Error> {
+        self.client
+            .write_asset_report_pdf(asset_report_token, writer)
+    }
+
+    pub fn create_audit_copy(
+        &self,
+        asset_report_token: &str,
+        auditor_id: &str,
+    ) -> Result<CreateAuditCopyTokenResponse, Error> {
+        self.client
+            .create_audit_copy(asset_report_token, auditor_id)
+    }
+
+    pub fn get_audit_copy(&self, audit_copy_token: &str) -> Result<GetAssetReportResponse, Error> {
+        self.client.get_audit_copy(audit_copy_token)
+    }
+
+    pub fn remove_audit_copy(
+        &self,
+        audit_copy_token: &str,
+    ) -> Result<RemoveAuditCopyResponse, Error> {
+        self.client.remove_audit_copy(audit_copy_token)
+    }
+
+    pub fn remove(&self, asset_report_token: &str) -> Result<RemoveAssetReportResponse, Error> {
+        self.client.remove_asset_report(asset_report_token)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AssetsApi<'_, AsyncClient> {
+    pub fn create(
+        &self,
+        access_tokens: &[&str],
+        days_requested: i64,
+        options: Option<AssetReportRequestOptions>,
+    ) -> ResponseFuture<CreateAssetReportResponse> {
+        self.client
+            .create_asset_report(access_tokens, days_requested, options)
+    }
+
+    pub fn refresh(
+        &self,
+        asset_report_token: &str,
+        days_requested: Option<i64>,
+        options: Option<AssetReportRequestOptions>,
+    ) -> ResponseFuture<RefreshAssetReportResponse> {
+        self.client
+            .refresh_asset_report(asset_report_token, days_requested, options)
+    }
+
+    pub fn filter(
+        &self,
+        asset_report_token: &str,
+        account_ids_to_exclude: &[&str],
+    ) -> ResponseFuture<FilterAssetReportResponse> {
+        self.client
+            .filter_asset_report(asset_report_token, account_ids_to_exclude)
+    }
+
+    pub fn get(&self, asset_report_token: &str) -> ResponseFuture<GetAssetReportResponse> {
+        self.client.get_asset_report(asset_report_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        asset_report_token: &str,
+        options: Option<GetAssetReportRequestOptions>,
+    ) -> ResponseFuture<GetAssetReportResponse> {
+        self.client
+            .get_asset_report_with_options(asset_report_token, options)
+    }
+
+    pub fn get_pdf(&self, asset_report_token: &str) -> ResponseFuture<Vec<u8>> {
+        self.client.get_asset_report_pdf(asset_report_token)
+    }
+
+    pub fn create_audit_copy(
+        &self,
+        asset_report_token: &str,
+        auditor_id: &str,
+    ) -> ResponseFuture<CreateAuditCopyTokenResponse> {
+        self.client
+            .create_audit_copy(asset_report_token, auditor_id)
+    }
+
+    pub fn get_audit_copy(&self, audit_copy_token: &str) -> ResponseFuture<GetAssetReportResponse> {
+        self.client.get_audit_copy(audit_copy_token)
+    }
+
+    pub fn remove_audit_copy(
+        &self,
+        audit_copy_token: &str,
+    ) -> ResponseFuture<RemoveAuditCopyResponse> {
+        self.client.remove_audit_copy(audit_copy_token)
+    }
+
+    pub fn remove(&self, asset_report_token: &str) -> ResponseFuture<RemoveAssetReportResponse> {
+        self.client.remove_asset_report(asset_report_token)
+    }
+}
+
+/// AuditCopy records that an auditor was given a copy of an asset report.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct AuditCopy {
+    pub asset_report_token: String,
+    pub auditor_id: String,
+    pub audit_copy_token: String,
+}
+
+/// AuditCopies creates and removes audit copies through a client, keeping
+/// track of which auditors hold copies of which reports, so every copy of a
+/// report can be revoked at once, e.g. when a loan is declined.
+///
+/// Only copies made or restored through it are tracked. Clones share the
+/// same copies.
+#[derive(Clone)]
+pub struct AuditCopies {
+    client: Client,
+    copies: Arc<Mutex<Vec<AuditCopy>>>,
+}
+
+impl AuditCopies {
+    pub fn new(client: Client) -> AuditCopies {
+        AuditCopies {
+            client,
+            copies: Arc::new(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Restores copies tracked earlier, e.g. loaded from a database.
+    pub fn with_copies(self, copies: Vec<AuditCopy>) -> AuditCopies {
+        self.copies.lock().unwrap().extend(copies);
+        self
+    }
+
+    /// Gives `auditor_id` a copy of the report and tracks it.
+    pub fn create(&self, asset_report_token: &str, auditor_id: &str) -> Result<AuditCopy, Error> {
+        let resp = self
+            .client
+            .create_audit_copy(asset_report_token, auditor_id)?;
+        let copy = AuditCopy {
+            asset_report_token: asset_report_token.to_string(),
+            auditor_id: auditor_id.to_string(),
+            audit_copy_token: resp.audit_copy_token,
+        };
+
+        self.copies.lock().unwrap().push(copy.clone());
+        Ok(copy)
+    }
+
+    /// Removes a copy, and stops tracking it once Plaid has removed it. If
+    /// Plaid answers that it did not remove the copy, it stays tracked and an
+    /// error is returned.
+    pub fn remove(&self, audit_copy_token: &str) -> Result<RemoveAuditCopyResponse, Error> {
+        let resp = self.client.remove_audit_copy(audit_copy_token)?;
+        if !resp.removed {
+            return Err(Error::new(Kind::NotRemoved("audit copy")));
+        }
+        self.copies
+            .lock()
+            .unwrap()
+            .retain(|copy| copy.audit_copy_token != audit_copy_token);
+        Ok(resp)
+    }
+
+    /// Removes a report, which also invalidates every copy of it, and stops
+    /// tracking its copies once Plaid has removed it. If Plaid answers that
+    /// it did not remove the report, its copies stay tracked and an error is
+    /// returned.
+    pub fn remove_asset_report(
+        &self,
+        asset_report_token: &str,
+    ) -> Result<RemoveAssetReportResponse, Error> {
+        let resp = self.client.remove_asset_report(asset_report_token)?;
+        if !resp.removed {
+            return Err(Error::new(Kind::NotRemoved("asset report")));
+        }
+        self.copies
+            .lock()
+            .unwrap()
+            .retain(|copy| copy.asset_report_token != asset_report_token);
+        Ok(resp)
+    }
+
+    /// Removes every tracked copy of a report and returns how many were
+    /// removed. It stops at the first copy that fails to be removed; copies
+    /// removed before then are no longer tracked.
+    pub fn remove_all(&self, asset_report_token: &str) -> Result<usize, Error> {
+        let copies = self.copies_of(asset_report_token);
+        for copy in &copies {
+            self.remove(&copy.audit_copy_token)?;
+        }
+        Ok(copies.len())
+    }
+
+    /// Returns the tracked copies of a report.
+    pub fn copies_of(&self, asset_report_token: &str) -> Vec<AuditCopy> {
+        self.copies
+            .lock()
+            .unwrap()
+            .iter()
+            .filter(|copy| copy.asset_report_token == asset_report_token)
+            .cloned()
+            .collect()
+    }
+
+    /// Returns the IDs of the auditors holding a copy of a report.
+    pub fn auditors(&self, asset_report_token: &str) -> Vec<String> {
+        self.copies_of(asset_report_token)
+            .into_iter()
+            .map(|copy| copy.auditor_id)
+            .collect()
+    }
+
+    /// Returns every tracked copy, e.g. to persist them.
+    pub fn copies(&self) -> Vec<AuditCopy> {
+        self.copies.lock().unwrap().clone()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::errors::ErrorCode;
+    use crate::transport::{
+        HttpRequest, HttpResponse, InMemoryTransport, StreamedResponse, Transport,
+    };
+    use serde_json::Value;
+    use std::io;
+
+    const ACCESS_TOKEN: &str = "access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6";
+    const ASSET_REPORT_TOKEN: &str = "assets-sandbox-6f12f5bb-22dd-4d23-a9e5-b4e1e7d1f5b9";
+
+    const ASSET_REPORT_CREATED: &str = r#"{
+        "asset_report_token": "assets-sandbox-6f12f5bb-22dd-4d23-a9e5-b4e1e7d1f5b9",
+        "asset_report_id": "1f414183-220c-44f5-b0c8-bc0e6d4053bb",
+        "request_id": "Iam3b"
+    }"#;
+
+    fn client(transport: &InMemoryTransport) -> Client {
+        Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+    }
+
+    #[test]
+    fn test_create_asset_report() {
+        let transport =
+            InMemoryTransport::new().with_response("/asset_report/create", ASSET_REPORT_CREATED);
+        let user = AssetReportUser {
+            client_user_id: Some("borrower-1".to_string()),
+            first_name: Some("Alberta".to_string()),
+            ..AssetReportUser::default()
+        };
+
+        let resp = client(&transport)
+            .create_asset_report(
+                &[ACCESS_TOKEN],
+                60,
+                Some(
+                    AssetReportRequestOptions::new()
+                        .with_client_report_id("loan-42")
+                        .with_user(user),
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(ASSET_REPORT_TOKEN, resp.asset_report_token);
+        let body: Value = serde_json::from_str(&transport.requests()[0].body).unwrap();
+        assert_eq!(serde_json::json!([ACCESS_TOKEN]), body["access_tokens"]);
+        assert_eq!(60, body["days_requested"]);
+        assert_eq!(
+            serde_json::json!({
+                "client_report_id": "loan-42",
+                "user": {"client_user_id": "borrower-1", "first_name": "Alberta"},
+            }),
+            body["options"]
+        );
+    }
+
+    #[test]
+    fn test_create_asset_report_validates() {
+        let transport = InMemoryTransport::new();
+
+        assert!(client(&transport)
+            .create_asset_report(&[], 60, None)
+            .is_err());
+        assert!(client(&transport)
+            .create_asset_report(&[ACCESS_TOKEN], 731, None)
+            .is_err());
+        assert!(transport.requests().is_empty());
+    }
+
+    #[test]
+    fn test_get_asset_report() {
+        let transport = InMemoryTransport::new().with_response(
+            "/asset_report/get",
+            include_str!("../fixtures/asset_report/get.json"),
+        );
+
+        let resp = client(&transport)
+            .get_asset_report_with_options(
+                ASSET_REPORT_TOKEN,
+                Some(GetAssetReportRequestOptions::new().with_include_insights(true)),
+            )
+            .unwrap();
+
+        let account = &resp.report.items[0].accounts[0];
+        assert_eq!("Plaid Saving", account.account.name);
+        assert_eq!(3.0, account.days_available);
+        assert_eq!(3, account.historical_balances.len());
+        assert_eq!("2019-08-21", account.historical_balances[1].date);
+        assert_eq!(vec!["Alberta Bobbeth Charleson"], account.owners[0].names);
+        assert_eq!(
+            Some("INTRST PYMNT"),
+            account.transactions[0].original_description.as_deref()
+        );
+        assert_eq!(Some("loan-42"), resp.report.client_report_id.as_deref());
+        assert_eq!(Some("123-45-6789"), resp.report.user.ssn.as_deref());
+        let body: Value = serde_json::from_str(&transport.requests()[0].body).unwrap();
+        assert_eq!(true, body["include_insights"]);
+    }
+
+    #[test]
+    fn test_get_asset_report_without_client_ids() {
+        let mut fixture: Value =
+            serde_json::from_str(include_str!("../fixtures/asset_report/get.json")).unwrap();
+        fixture["report"]["client_report_id"] = Value::Null;
+        fixture["report"]["user"] = serde_json::json!({
+            "client_user_id": null,
+            "email": null,
+            "first_name": "Alberta",
+            "last_name": null,
+            "middle_name": null,
+            "phone_number": null,
+            "ssn": null
+        });
+        let transport =
+            InMemoryTransport::new().with_response("/asset_report/get", &fixture.to_string());
+
+        let resp = client(&transport)
+            .get_asset_report(ASSET_REPORT_TOKEN)
+            .unwrap();
+
+        assert!(resp.report.client_report_id.is_none());
+        assert!(resp.report.user.client_user_id.is_none());
+        assert!(resp.report.user.ssn.is_none());
+        assert_eq!(Some("Alberta"), resp.report.user.first_name.as_deref());
+        assert_eq!(1, resp.report.items.len());
+    }
+
+    #[test]
+    fn test_write_asset_report_pdf() {
+        let pdf = b"%PDF-1.4\n\xe2\xe3\xcf\xd3\n%%EOF";
+        let transport = InMemoryTransport::new();
+        transport.push_response(
+            "/asset_report/pdf/get",
+            HttpResponse {
+                status: 200,
+                headers: vec![("Content-Type".to_string(), "application/pdf".to_string())],
+                body: pdf.to_vec(),
+            },
+        );
+
+        let mut written = Vec::new();
+        let len = client(&transport)
+            .write_asset_report_pdf(ASSET_REPORT_TOKEN, &mut written)
+            .unwrap();
+
+        assert_eq!(pdf.len() as u64, len);
+        assert_eq!(pdf.to_vec(), written);
+    }
+
+    #[test]
+    fn test_get_asset_report_pdf_decodes_errors() {
+        let transport = InMemoryTransport::new().with_status_response(
+            "/asset_report/pdf/get",
+            400,
+            r#"{
+                "display_message": null,
+                "error_code": "PRODUCT_NOT_READY",
+                "error_message": "the requested product is not yet ready",
+                "error_type": "ASSET_REPORT_ERROR",
+                "request_id": "Bxj2vM3E5gaj8iB"
+            }"#,
+        );
+
+        let err = client(&transport)
+            .assets()
+            .get_pdf(ASSET_REPORT_TOKEN)
+            .unwrap_err();
+
+        assert_eq!(Some(&ErrorCode::ProductNotReady), err.error_code());
+
+        let mut written = Vec::new();
+        let err = client(&transport)
+            .assets()
+            .write_pdf(ASSET_REPORT_TOKEN, &mut written)
+            .unwrap_err();
+
+        assert_eq!(Some(&ErrorCode::ProductNotReady), err.error_code());
+        assert!(written.is_empty());
+    }
+
+    // StreamingTransport only answers requests through `send_streamed`.
+    struct StreamingTransport(&'static [u8]);
+
+    impl Transport for StreamingTransport {
+        fn send(&self, _: HttpRequest) -> Result<HttpResponse, Error> {
+            Err(Error::new(Kind::Transport("body read in full".to_string())))
+        }
+
+        fn send_streamed(&self, _: HttpRequest) -> Result<StreamedResponse, Error> {
+            Ok(StreamedResponse {
+                status: 200,
+                headers: vec![("Content-Type".to_string(), "application/pdf".to_string())],
+                body: Box::new(io::Cursor::new(self.0)),
+            })
+        }
+    }
+
+    #[test]
+    fn test_write_asset_report_pdf_streams_body() {
+        let pdf = b"%PDF-1.4\n\xe2\xe3\xcf\xd3\n%%EOF";
+        let client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(StreamingTransport(pdf));
+
+        let mut written = Vec::new();
+        let len = client
+            .write_asset_report_pdf(ASSET_REPORT_TOKEN, &mut written)
+            .unwrap();
+
+        assert_eq!(pdf.len() as u64, len);
+        assert_eq!(pdf.to_vec(), written);
+
+        // Body logging needs the whole body, so it is not streamed.
+        let err = client
+            .with_body_logging(true)
+            .write_asset_report_pdf(ASSET_REPORT_TOKEN, &mut Vec::new())
+            .unwrap_err();
+        assert!(err.to_string().contains("body read in full"));
+    }
+
+    #[test]
+    fn test_audit_copies() {
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/asset_report/audit_copy/create",
+                r#"{"audit_copy_token": "a-sandbox-3TAU2CWVYBDVRHUCAAAI27ULU4", "request_id": "Iam3b"}"#,
+            )
+            .with_response(
+                "/asset_report/audit_copy/create",
+                r#"{"audit_copy_token": "a-sandbox-7ZIEJ4VOEFEQJ7GGK3SLV2MZ5E", "request_id": "Iam3c"}"#,
+            )
+            .with_response(
+                "/asset_report/audit_copy/remove",
+                r#"{"removed": true, "request_id": "m8MDnv9okwxFNBV"}"#,
+            );
+        let audit_copies = AuditCopies::new(client(&transport));
+
+        audit_copies
+            .create(ASSET_REPORT_TOKEN, "fannie_mae")
+            .unwrap();
+        audit_copies
+            .create(ASSET_REPORT_TOKEN, "freddie_mac")
+            .unwrap();
+        assert_eq!(
+            vec!["fannie_mae", "freddie_mac"],
+            audit_copies.auditors(ASSET_REPORT_TOKEN)
+        );
+
+        assert_eq!(2, audit_copies.remove_all(ASSET_REPORT_TOKEN).unwrap());
+        assert!(audit_copies.auditors(ASSET_REPORT_TOKEN).is_empty());
+        let removed: Vec<Value> = transport
+            .requests_to("/asset_report/audit_copy/remove")
+            .iter()
+            .map(|request| serde_json::from_str(&request.body).unwrap())
+            .collect();
+        assert_eq!(
+            "a-sandbox-7ZIEJ4VOEFEQJ7GGK3SLV2MZ5E",
+            removed[1]["audit_copy_token"]
+        );
+    }
+
+    #[test]
+    fn test_audit_copies_keep_copies_not_removed() {
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/asset_report/audit_copy/create",
+                r#"{"audit_copy_token": "a-sandbox-3TAU2CWVYBDVRHUCAAAI27ULU4", "request_id": "Iam3b"}"#,
+            )
+            .with_response(
+                "/asset_report/audit_copy/remove",
+                r#"{"removed": false, "request_id": "m8MDnv9okwxFNBV"}"#,
+            )
+            .with_response(
+                "/asset_report/remove",
+                r#"{"removed": false, "request_id": "m8MDnv9okwxFNBW"}"#,
+            );
+        let audit_copies = AuditCopies::new(client(&transport));
+        let copy = audit_copies
+            .create(ASSET_REPORT_TOKEN, "fannie_mae")
+            .unwrap();
+
+        assert!(audit_copies.remove(&copy.audit_copy_token).is_err());
+        assert!(audit_copies
+            .remove_asset_report(ASSET_REPORT_TOKEN)
+            .is_err());
+        assert_eq!(vec![copy], audit_copies.copies());
+    }
+
+    #[test]
+    fn test_audit_copies_remove_asset_report() {
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/asset_report/audit_copy/create",
+                r#"{"audit_copy_token": "a-sandbox-3TAU2CWVYBDVRHUCAAAI27ULU4", "request_id": "Iam3b"}"#,
+            )
+            .with_response(
+                "/asset_report/remove",
+                r#"{"removed": true, "request_id": "m8MDnv9okwxFNBV"}"#,
+            );
+        let audit_copies = AuditCopies::new(client(&transport)).with_copies(vec![AuditCopy {
+            asset_report_token: "assets-sandbox-other".to_string(),
+            auditor_id: "fannie_mae".to_string(),
+            audit_copy_token: "a-sandbox-7ZIEJ4VOEFEQJ7GGK3SLV2MZ5E".to_string(),
+        }]);
+        audit_copies
+            .create(ASSET_REPORT_TOKEN, "fannie_mae")
+            .unwrap();
+
+        audit_copies
+            .remove_asset_report(ASSET_REPORT_TOKEN)
+            .unwrap();
+
+        assert!(audit_copies.copies_of(ASSET_REPORT_TOKEN).is_empty());
+        assert_eq!(1, audit_copies.copies_of("assets-sandbox-other").len());
+    }
+
+    #[test]
+    fn test_refresh_and_filter_asset_report() {
+        let transport = InMemoryTransport::new()
+            .with_response("/asset_report/refresh", ASSET_REPORT_CREATED)
+            .with_response("/asset_report/filter", ASSET_REPORT_CREATED);
+        let test_client = client(&transport);
+
+        test_client
+            .assets()
+            .refresh(ASSET_REPORT_TOKEN, Some(30), None)
+            .unwrap();
+        test_client
+            .assets()
+            .filter(
+                ASSET_REPORT_TOKEN,
+                &["QPO8Jo8vdDHMepg41PBwckXm4KdK1yUdmXOwK"],
+            )
+            .unwrap();
+
+        let requests = transport.requests();
+        let refresh: Value = serde_json::from_str(&requests[0].body).unwrap();
+        assert_eq!(30, refresh["days_requested"]);
+        assert!(refresh.get("options").is_none());
+        let filter: Value = serde_json::from_str(&requests[1].body).unwrap();
+        assert_eq!(
+            serde_json::json!(["QPO8Jo8vdDHMepg41PBwckXm4KdK1yUdmXOwK"]),
+            filter["account_ids_to_exclude"]
+        );
     }
 }
@@ -1,6 +1,9 @@
 use crate::accounts::{ACHNumber, Account, BACSNumber, EFTNumber, IBANNumber};
+use crate::endpoint::Endpoint;
 use crate::errors::{Error, Kind};
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
 use serde::{Deserialize, Serialize};
 
 pub trait Auth<'a> {
@@ -14,19 +17,34 @@ pub trait Auth<'a> {
 
 #[derive(Serialize)]
 struct GetAuthRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<GetAuthRequestOptions<'a>>,
 }
 
-#[derive(Serialize)]
+impl Endpoint for GetAuthRequest<'_> {
+    type Response = GetAuthResponse;
+    const PATH: &'static str = "/auth/get";
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct GetAuthRequestOptions<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     account_ids: Option<Vec<&'a str>>,
 }
 
+impl<'a> GetAuthRequestOptions<'a> {
+    pub fn new() -> GetAuthRequestOptions<'a> {
+        GetAuthRequestOptions::default()
+    }
+
+    /// Only returns data for the accounts with these IDs.
+    pub fn with_account_ids(mut self, account_ids: Vec<&'a str>) -> GetAuthRequestOptions<'a> {
+        self.account_ids = Some(account_ids);
+        self
+    }
+}
+
 #[derive(Deserialize)]
 pub struct AccountNumberCollection {
     pub ach: Vec<ACHNumber>,
@@ -42,26 +60,22 @@ pub struct GetAuthResponse {
     pub numbers: AccountNumberCollection,
 }
 
-impl<'a> Auth<'a> for Client<'a> {
+impl<'a> Auth<'a> for Client {
     fn get_auth_with_options(
         &self,
         access_token: &str,
         options: Option<GetAuthRequestOptions>,
     ) -> Result<GetAuthResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
         let req = GetAuthRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/auth/get", &json_body))
+        self.send(&req)
     }
 
     fn get_auth(&self, access_token: &str) -> Result<GetAuthResponse, Error> {
@@ -69,26 +83,101 @@ impl<'a> Auth<'a> for Client<'a> {
     }
 }
 
+#[cfg(feature = "async")]
+pub trait AsyncAuth {
+    fn get_auth_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetAuthRequestOptions>,
+    ) -> ResponseFuture<GetAuthResponse>;
+    fn get_auth(&self, access_token: &str) -> ResponseFuture<GetAuthResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncAuth for AsyncClient {
+    fn get_auth_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetAuthRequestOptions>,
+    ) -> ResponseFuture<GetAuthResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetAuthRequest {
+            access_token,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn get_auth(&self, access_token: &str) -> ResponseFuture<GetAuthResponse> {
+        self.get_auth_with_options(access_token, None)
+    }
+}
+
+/// AuthApi groups the auth endpoints, e.g.
+/// `client.auth().get(..)`.
+pub struct AuthApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl AuthApi<'_, Client> {
+    pub fn get(&self, access_token: &str) -> Result<GetAuthResponse, Error> {
+        self.client.get_auth(access_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetAuthRequestOptions>,
+    ) -> Result<GetAuthResponse, Error> {
+        self.client.get_auth_with_options(access_token, options)
+    }
+}
+
+#[cfg(feature = "async")]
+impl AuthApi<'_, AsyncClient> {
+    pub fn get(&self, access_token: &str) -> ResponseFuture<GetAuthResponse> {
+        self.client.get_auth(access_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetAuthRequestOptions>,
+    ) -> ResponseFuture<GetAuthResponse> {
+        self.client.get_auth_with_options(access_token, options)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
     use crate::environments::Environment;
     use crate::items::Items;
     use crate::sandbox::Sandbox;
-    use std::env;
+    use crate::transport::InMemoryTransport;
 
     #[test]
     fn test_get_auth() {
-        let client_id = env::var("PLAID_CLIENT_ID").unwrap();
-        let secret = env::var("PLAID_SECRET").unwrap();
-        let public_key = env::var("PLAID_PUBLIC_KEY").unwrap();
-        let test_client = Client {
-            client_id: client_id.as_str(),
-            secret: secret.as_str(),
-            public_key: public_key.as_str(),
-            environment: Environment::SANDBOX,
-            http_client: reqwest::Client::new(),
-        };
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/sandbox/public_token/create",
+                include_str!("../fixtures/sandbox/public_token/create.json"),
+            )
+            .with_response(
+                "/item/public_token/exchange",
+                include_str!("../fixtures/item/public_token/exchange.json"),
+            )
+            .with_response("/auth/get", include_str!("../fixtures/auth/get.json"))
+            .with_response(
+                "/auth/get",
+                include_str!("../fixtures/auth/get_filtered.json"),
+            );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport);
 
         let sandbox_resp = test_client
             .create_sandbox_public_token(
@@ -0,0 +1,102 @@
+//! plaid-mock serves the Plaid endpoints this crate wraps, apart from asset
+//! reports, from an in-memory model, so clients can be pointed at it instead
+//! of Plaid, e.g. with `PLAID_ENV=http://127.0.0.1:4010`. It is built with the
+//! `mock` feature: `cargo run --features mock --bin plaid-mock`.
+//!
+//! Usage: plaid-mock [--addr ADDR] [--client-id ID --secret SECRET]
+//!
+//! The address defaults to `PLAID_MOCK_ADDR`, or 127.0.0.1:4010. Without
+//! credentials, any client ID and secret are accepted.
+
+use hyper::rt::{self, Future, Stream};
+use hyper::service::service_fn;
+use hyper::{Body, Method, Request, Response, Server};
+use plaid_rust::mock::MockPlaid;
+use std::env;
+use std::net::SocketAddr;
+use std::process;
+
+const DEFAULT_ADDR: &str = "127.0.0.1:4010";
+
+struct Args {
+    addr: SocketAddr,
+    credentials: Option<(String, String)>,
+}
+
+fn main() {
+    let args = parse_args().unwrap_or_else(|err| {
+        eprintln!("plaid-mock: {}", err);
+        eprintln!("usage: plaid-mock [--addr ADDR] [--client-id ID --secret SECRET]");
+        process::exit(2);
+    });
+
+    let mut mock = MockPlaid::new();
+    if let Some((ref client_id, ref secret)) = args.credentials {
+        mock = mock.with_credentials(client_id, secret);
+    }
+
+    let new_service = move || {
+        let mock = mock.clone();
+        service_fn(move |request: Request<Body>| {
+            let mock = mock.clone();
+            let method = request.method().clone();
+            let path = request.uri().path().to_string();
+
+            request.into_body().concat2().map(move |body| {
+                if method != Method::POST {
+                    return Response::builder().status(405).body(Body::empty()).unwrap();
+                }
+
+                let response = mock.handle(&path, &String::from_utf8_lossy(&body));
+                let mut builder = Response::builder();
+                builder.status(response.status);
+                for (name, value) in &response.headers {
+                    builder.header(name.as_str(), value.as_str());
+                }
+                builder.body(Body::from(response.body)).unwrap()
+            })
+        })
+    };
+
+    let server = Server::try_bind(&args.addr)
+        .unwrap_or_else(|err| {
+            eprintln!("plaid-mock: cannot listen on {}: {}", args.addr, err);
+            process::exit(1);
+        })
+        .serve(new_service)
+        .map_err(|err| eprintln!("plaid-mock: {}", err));
+
+    println!("plaid-mock listening on http://{}", args.addr);
+    rt::run(server);
+}
+
+fn parse_args() -> Result<Args, String> {
+    let mut addr = env::var("PLAID_MOCK_ADDR").unwrap_or_else(|_| DEFAULT_ADDR.to_string());
+    let mut client_id = None;
+    let mut secret = None;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = |name: &str| {
+            args.next()
+                .ok_or_else(|| format!("{} requires a value", name))
+        };
+        match arg.as_str() {
+            "--addr" => addr = value("--addr")?,
+            "--client-id" => client_id = Some(value("--client-id")?),
+            "--secret" => secret = Some(value("--secret")?),
+            _ => return Err(format!("unexpected argument {}", arg)),
+        }
+    }
+
+    let credentials = match (client_id, secret) {
+        (Some(client_id), Some(secret)) => Some((client_id, secret)),
+        (None, None) => None,
+        _ => return Err("--client-id and --secret must be given together".to_string()),
+    };
+    let addr = addr
+        .parse()
+        .map_err(|err| format!("invalid address {}: {}", addr, err))?;
+
+    Ok(Args { addr, credentials })
+}
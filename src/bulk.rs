@@ -0,0 +1,220 @@
+use crate::errors::Error;
+use crate::plaid::Client;
+use crate::rate_limit::RateLimiter;
+use std::collections::BTreeMap;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, Instant};
+
+/// Bulk runs one operation for many items at once, e.g. refreshing balances
+/// for every access token a platform holds. At most `concurrency` calls are in
+/// flight at a time, and every call goes through the client's retry policy and
+/// rate limiter.
+///
+/// The operation is given the client and an access token, e.g.
+/// `|client, access_token| client.get_balances(access_token)`.
+#[derive(Clone)]
+pub struct Bulk {
+    client: Client,
+    concurrency: usize,
+}
+
+/// BulkResult is the outcome of the operation for one access token.
+#[derive(Debug)]
+pub struct BulkResult<T> {
+    pub access_token: String,
+    pub result: Result<T, Error>,
+}
+
+/// BulkStats sums up a bulk run. Failures are counted by Plaid `error_code`,
+/// or by `TIMEOUT`, `NETWORK` or `OTHER` for calls Plaid never answered with
+/// an error body.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct BulkStats {
+    pub total: usize,
+    pub succeeded: usize,
+    pub failed: usize,
+    pub errors_by_code: BTreeMap<String, usize>,
+    pub elapsed: Duration,
+}
+
+/// BulkResults holds a result for every access token, in the order the tokens
+/// were given.
+#[derive(Debug)]
+pub struct BulkResults<T> {
+    pub results: Vec<BulkResult<T>>,
+    pub stats: BulkStats,
+}
+
+impl<T> BulkResults<T> {
+    /// Returns the results of the operations that succeeded.
+    pub fn succeeded(&self) -> impl Iterator<Item = (&str, &T)> {
+        self.results
+            .iter()
+            .filter_map(|result| match result.result {
+                Ok(ref value) => Some((result.access_token.as_str(), value)),
+                Err(_) => None,
+            })
+    }
+
+    /// Returns the errors of the operations that failed.
+    pub fn failed(&self) -> impl Iterator<Item = (&str, &Error)> {
+        self.results
+            .iter()
+            .filter_map(|result| match result.result {
+                Ok(_) => None,
+                Err(ref err) => Some((result.access_token.as_str(), err)),
+            })
+    }
+}
+
+impl Bulk {
+    /// Runs operations through `client`, four at a time.
+    pub fn new(client: Client) -> Bulk {
+        Bulk {
+            client,
+            concurrency: 4,
+        }
+    }
+
+    /// Caps how many calls are in flight at once.
+    pub fn with_concurrency(mut self, concurrency: usize) -> Bulk {
+        self.concurrency = concurrency.max(1);
+        self
+    }
+
+    /// Throttles the run through `rate_limiter`, in place of any limiter the
+    /// client already has.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Bulk {
+        self.client = self.client.with_rate_limiter(rate_limiter);
+        self
+    }
+
+    /// Calls `operation` once for every access token and waits for all of
+    /// them to finish.
+    pub fn run<S, T, F>(&self, access_tokens: &[S], operation: F) -> BulkResults<T>
+    where
+        S: AsRef<str> + Sync,
+        T: Send,
+        F: Fn(&Client, &str) -> Result<T, Error> + Sync,
+    {
+        let started_at = Instant::now();
+        let next = AtomicUsize::new(0);
+        let slots: Vec<Mutex<Option<Result<T, Error>>>> =
+            access_tokens.iter().map(|_| Mutex::new(None)).collect();
+
+        // Scoped threads, stable since Rust 1.63 and the reason for the
+        // crate's rust-version, let the workers borrow the tokens and the
+        // operation instead of requiring them to be 'static.
+        thread::scope(|scope| {
+            for _ in 0..self.concurrency.min(access_tokens.len()) {
+                scope.spawn(|| loop {
+                    let i = next.fetch_add(1, Ordering::SeqCst);
+                    if i >= access_tokens.len() {
+                        break;
+                    }
+                    let result = operation(&self.client, access_tokens[i].as_ref());
+                    *slots[i].lock().unwrap() = Some(result);
+                });
+            }
+        });
+
+        let mut stats = BulkStats {
+            total: access_tokens.len(),
+            ..BulkStats::default()
+        };
+        let results = access_tokens
+            .iter()
+            .zip(slots)
+            .map(|(access_token, slot)| {
+                let result = slot
+                    .into_inner()
+                    .unwrap()
+                    .expect("every access token is processed");
+                match result {
+                    Ok(_) => stats.succeeded += 1,
+                    Err(ref err) => {
+                        stats.failed += 1;
+                        *stats.errors_by_code.entry(error_code(err)).or_insert(0) += 1;
+                    }
+                }
+                BulkResult {
+                    access_token: access_token.as_ref().to_string(),
+                    result,
+                }
+            })
+            .collect();
+        stats.elapsed = started_at.elapsed();
+
+        BulkResults { results, stats }
+    }
+}
+
+fn error_code(err: &Error) -> String {
+    match err.error_code() {
+        Some(code) => code.as_str().to_string(),
+        None if err.is_timeout() => "TIMEOUT".to_string(),
+        None if err.is_network() => "NETWORK".to_string(),
+        None => "OTHER".to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::items::Items;
+    use crate::mock::MockPlaid;
+
+    #[test]
+    fn test_run() {
+        let client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(MockPlaid::new());
+        let access_tokens = vec![
+            "access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6",
+            "access-sandbox-unknown",
+            "access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6",
+        ];
+
+        let results = Bulk::new(client)
+            .with_concurrency(2)
+            .run(&access_tokens, |client, access_token| {
+                client.get_item(access_token)
+            });
+
+        assert_eq!(3, results.stats.total);
+        assert_eq!(2, results.stats.succeeded);
+        assert_eq!(1, results.stats.failed);
+        assert_eq!(
+            Some(&1),
+            results.stats.errors_by_code.get("INVALID_ACCESS_TOKEN")
+        );
+        assert_eq!("access-sandbox-unknown", results.results[1].access_token);
+        assert!(results.results[1].result.is_err());
+        assert_eq!(2, results.succeeded().count());
+    }
+
+    #[test]
+    fn test_run_bounds_concurrency() {
+        let client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX);
+        let access_tokens: Vec<String> = (0..20).map(|i| format!("access-{}", i)).collect();
+        let in_flight = AtomicUsize::new(0);
+        let most_in_flight = AtomicUsize::new(0);
+
+        let results =
+            Bulk::new(client)
+                .with_concurrency(3)
+                .run(&access_tokens, |_, access_token| {
+                    let now = in_flight.fetch_add(1, Ordering::SeqCst) + 1;
+                    most_in_flight.fetch_max(now, Ordering::SeqCst);
+                    thread::sleep(Duration::from_millis(5));
+                    in_flight.fetch_sub(1, Ordering::SeqCst);
+                    Ok(access_token.len())
+                });
+
+        assert_eq!(20, results.stats.succeeded);
+        assert!(most_in_flight.load(Ordering::SeqCst) <= 3);
+        assert_eq!("access-7", results.results[7].access_token);
+    }
+}
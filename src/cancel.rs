@@ -0,0 +1,71 @@
+use futures::task::{self, Task};
+use futures::{Async, Future, Poll};
+use std::sync::{Arc, Mutex};
+
+/// CancellationToken cancels the calls of an `AsyncClient` it was given to.
+/// Once cancelled, every pending call, including any retry or rate limit wait
+/// it is in, fails with an error for which `Error::is_cancelled` is true, and
+/// new calls fail straight away.
+///
+/// Dropping a response future also abandons its call; the token is for
+/// cancelling calls whose futures are owned elsewhere. Clones share their
+/// state.
+#[derive(Clone, Default)]
+pub struct CancellationToken {
+    inner: Arc<Mutex<CancelState>>,
+}
+
+#[derive(Default)]
+struct CancelState {
+    cancelled: bool,
+    waiters: Vec<Task>,
+}
+
+impl CancellationToken {
+    pub fn new() -> CancellationToken {
+        CancellationToken::default()
+    }
+
+    pub fn cancel(&self) {
+        let mut state = self.inner.lock().unwrap();
+        state.cancelled = true;
+        for waiter in state.waiters.drain(..) {
+            waiter.notify();
+        }
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.inner.lock().unwrap().cancelled
+    }
+
+    // cancelled resolves once the token is cancelled.
+    pub(crate) fn cancelled(&self) -> Cancelled {
+        Cancelled {
+            token: self.clone(),
+        }
+    }
+}
+
+pub(crate) struct Cancelled {
+    token: CancellationToken,
+}
+
+impl Future for Cancelled {
+    type Item = ();
+    type Error = ();
+
+    fn poll(&mut self) -> Poll<(), ()> {
+        let mut state = self.token.inner.lock().unwrap();
+        if state.cancelled {
+            return Ok(Async::Ready(()));
+        }
+        if !state
+            .waiters
+            .iter()
+            .any(|waiter| waiter.will_notify_current())
+        {
+            state.waiters.push(task::current());
+        }
+        Ok(Async::NotReady)
+    }
+}
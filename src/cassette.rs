@@ -0,0 +1,532 @@
+use crate::errors::{Error, Kind};
+use crate::logging::redact_fields;
+use crate::middleware::Middleware;
+use crate::transport::{HttpRequest, HttpResponse};
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+const SCRUBBED: &str = "[SCRUBBED]";
+const REDACTED_PREFIX: &str = "[REDACTED:";
+
+// Credentials are replaced before a request is written to a cassette or
+// matched against one, so cassettes can be replayed with any credentials.
+const CREDENTIAL_FIELDS: &[&str] = &["client_id", "secret", "public_key"];
+
+/// Cassette records the calls a client makes to a file, and replays them
+/// later without touching the network. Add it to a client with
+/// `with_middleware`.
+///
+/// Interactions are keyed by endpoint and request body, with credentials
+/// scrubbed and object keys sorted. Tokens, account numbers and identity data
+/// are never written: the fields `logging::redact` masks are replaced, in
+/// requests and JSON responses alike, with a placeholder derived from their
+/// value. An access token returned by a recorded exchange therefore replays as
+/// the same placeholder the later recorded requests were keyed by, and
+/// cassettes can be committed. Bodies that are not UTF-8, such as asset report
+/// PDFs, are stored base64 encoded.
+///
+/// When the same request was recorded more than once, replay serves the
+/// responses in order and repeats the last one. A request with no recording
+/// fails with an error describing how it differs from the closest recorded
+/// request to the same endpoint.
+///
+/// Clones share the same recording.
+#[derive(Clone)]
+pub struct Cassette {
+    inner: Arc<Mutex<State>>,
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Mode {
+    Record,
+    Replay,
+}
+
+struct State {
+    mode: Mode,
+    path: PathBuf,
+    interactions: Vec<Interaction>,
+    served: HashMap<String, usize>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct File {
+    interactions: Vec<Interaction>,
+}
+
+#[derive(Clone, Serialize, Deserialize)]
+struct Interaction {
+    endpoint: String,
+    request: Value,
+    status: u16,
+    #[serde(default)]
+    headers: Vec<(String, String)>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    json: Option<Value>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    text: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    base64: Option<String>,
+}
+
+impl Interaction {
+    fn key(&self) -> String {
+        format!("{} {}", self.endpoint, self.request)
+    }
+
+    fn response(&self) -> Result<HttpResponse, Error> {
+        let body = match (&self.json, &self.text, &self.base64) {
+            (Some(json), _, _) => json.to_string().into_bytes(),
+            (None, Some(text), _) => text.clone().into_bytes(),
+            (None, None, Some(encoded)) => base64::decode(encoded).map_err(|err| {
+                Error::new(Kind::Cassette(format!(
+                    "invalid base64 body recorded for {}: {}",
+                    self.endpoint, err
+                )))
+            })?,
+            (None, None, None) => Vec::new(),
+        };
+        Ok(HttpResponse {
+            status: self.status,
+            headers: self.headers.clone(),
+            body,
+        })
+    }
+}
+
+impl Cassette {
+    /// Starts a new recording, written to `path` after every call. Any
+    /// cassette already at `path` is replaced.
+    pub fn record<P: AsRef<Path>>(path: P) -> Cassette {
+        Cassette::with_state(Mode::Record, path.as_ref(), Vec::new())
+    }
+
+    /// Loads the cassette at `path` for replay.
+    pub fn replay<P: AsRef<Path>>(path: P) -> Result<Cassette, Error> {
+        let path = path.as_ref();
+        let contents = fs::read_to_string(path).map_err(|err| Error::new(Kind::Io(err)))?;
+        let file: File =
+            serde_json::from_str(&contents).map_err(|err| Error::new(Kind::Json(err)))?;
+        Ok(Cassette::with_state(Mode::Replay, path, file.interactions))
+    }
+
+    /// Replays the cassette at `path` if there is one, and records a new one
+    /// otherwise.
+    pub fn replay_or_record<P: AsRef<Path>>(path: P) -> Result<Cassette, Error> {
+        if path.as_ref().exists() {
+            Cassette::replay(path)
+        } else {
+            Ok(Cassette::record(path))
+        }
+    }
+
+    fn with_state(mode: Mode, path: &Path, interactions: Vec<Interaction>) -> Cassette {
+        Cassette {
+            inner: Arc::new(Mutex::new(State {
+                mode,
+                path: path.to_path_buf(),
+                interactions,
+                served: HashMap::new(),
+            })),
+        }
+    }
+
+    /// Returns true if the cassette is serving recorded responses.
+    pub fn is_replaying(&self) -> bool {
+        self.inner.lock().unwrap().mode == Mode::Replay
+    }
+}
+
+impl Middleware for Cassette {
+    fn on_request(&self, request: &mut HttpRequest) -> Option<Result<HttpResponse, Error>> {
+        let mut state = self.inner.lock().unwrap();
+        if state.mode != Mode::Replay {
+            return None;
+        }
+        Some(state.replay(request))
+    }
+
+    fn on_response(
+        &self,
+        request: &HttpRequest,
+        result: &mut Result<HttpResponse, Error>,
+        _latency: Duration,
+    ) {
+        let mut state = self.inner.lock().unwrap();
+        if state.mode != Mode::Record {
+            return;
+        }
+
+        // Only responses are recorded; transport errors say nothing about
+        // what Plaid would have answered.
+        if let Ok(ref response) = *result {
+            let mut interaction = Interaction {
+                endpoint: request.endpoint.clone(),
+                request: normalize_body(&request.body),
+                status: response.status,
+                headers: response.headers.clone(),
+                json: None,
+                text: None,
+                base64: None,
+            };
+            match serde_json::from_slice::<Value>(&response.body) {
+                Ok(mut json) => {
+                    redact_fields(&mut json, &mask);
+                    interaction.json = Some(json);
+                }
+                Err(_) => match String::from_utf8(response.body.clone()) {
+                    Ok(text) => interaction.text = Some(text),
+                    Err(_) => interaction.base64 = Some(base64::encode(&response.body)),
+                },
+            }
+            state.interactions.push(interaction);
+            if let Err(err) = state.save() {
+                *result = Err(err);
+            }
+        }
+    }
+}
+
+impl State {
+    fn replay(&mut self, request: &HttpRequest) -> Result<HttpResponse, Error> {
+        let body = normalize_body(&request.body);
+        let key = format!("{} {}", request.endpoint, body);
+
+        let matching: Vec<&Interaction> = self
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.key() == key)
+            .collect();
+        if matching.is_empty() {
+            return Err(self.mismatch(&request.endpoint, &body));
+        }
+
+        let served = self.served.entry(key).or_insert(0);
+        let interaction = matching[(*served).min(matching.len() - 1)];
+        *served += 1;
+        interaction.response()
+    }
+
+    // mismatch explains why a request has no recording, by diffing it against
+    // the recorded request to the same endpoint that differs the least.
+    fn mismatch(&self, endpoint: &str, body: &Value) -> Error {
+        let actual = flatten(body);
+        let closest = self
+            .interactions
+            .iter()
+            .filter(|interaction| interaction.endpoint == endpoint)
+            .map(|interaction| diff(&flatten(&interaction.request), &actual))
+            .min_by_key(|lines| lines.len());
+
+        let message = match closest {
+            Some(lines) => format!(
+                "no recorded request to {} in {} matches (- recorded, + actual):\n{}",
+                endpoint,
+                self.path.display(),
+                lines.join("\n")
+            ),
+            None => format!(
+                "no request to {} was recorded in {}",
+                endpoint,
+                self.path.display()
+            ),
+        };
+        Error::new(Kind::Cassette(message))
+    }
+
+    fn save(&self) -> Result<(), Error> {
+        let file = File {
+            interactions: self.interactions.clone(),
+        };
+        let contents =
+            serde_json::to_string_pretty(&file).map_err(|err| Error::new(Kind::Json(err)))?;
+        if let Some(dir) = self.path.parent() {
+            fs::create_dir_all(dir).map_err(|err| Error::new(Kind::Io(err)))?;
+        }
+        fs::write(&self.path, contents).map_err(|err| Error::new(Kind::Io(err)))
+    }
+}
+
+// normalize_body parses a request body, masks its sensitive fields and scrubs
+// its credentials. Object keys come out sorted, so field order does not affect
+// matching.
+fn normalize_body(body: &str) -> Value {
+    let mut value = serde_json::from_str(body).unwrap_or_else(|_| Value::String(body.to_string()));
+    redact_fields(&mut value, &mask);
+    if let Value::Object(ref mut fields) = value {
+        for name in CREDENTIAL_FIELDS {
+            if let Some(field) = fields.get_mut(*name) {
+                *field = Value::String(SCRUBBED.to_string());
+            }
+        }
+    }
+    value
+}
+
+// mask replaces every string in a sensitive field with a placeholder, leaving
+// the shape of the field alone so the recorded response still decodes.
+// Placeholders are left as they are, since replayed responses hand them back
+// to the caller as tokens.
+fn mask(value: &mut Value) {
+    match *value {
+        Value::String(ref mut text) if !text.starts_with(REDACTED_PREFIX) && text != SCRUBBED => {
+            *text = placeholder(text);
+        }
+        Value::Array(ref mut items) => items.iter_mut().for_each(mask),
+        Value::Object(ref mut fields) => fields.values_mut().for_each(mask),
+        _ => {}
+    }
+}
+
+// placeholder derives a stable stand-in for a sensitive value from its 64-bit
+// FNV-1a hash, so the same value is masked the same way in every cassette.
+fn placeholder(value: &str) -> String {
+    let hash = value.bytes().fold(0xcbf2_9ce4_8422_2325_u64, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+    });
+    format!("{}{:016x}]", REDACTED_PREFIX, hash)
+}
+
+// flatten lists every leaf of a JSON value by its path, e.g.
+// `options.account_ids[0]`.
+fn flatten(value: &Value) -> BTreeMap<String, String> {
+    fn walk(path: String, value: &Value, leaves: &mut BTreeMap<String, String>) {
+        match value {
+            Value::Object(fields) if !fields.is_empty() => {
+                for (name, field) in fields {
+                    let path = if path.is_empty() {
+                        name.clone()
+                    } else {
+                        format!("{}.{}", path, name)
+                    };
+                    walk(path, field, leaves);
+                }
+            }
+            Value::Array(items) if !items.is_empty() => {
+                for (i, item) in items.iter().enumerate() {
+                    walk(format!("{}[{}]", path, i), item, leaves);
+                }
+            }
+            _ => {
+                leaves.insert(path, value.to_string());
+            }
+        }
+    }
+
+    let mut leaves = BTreeMap::new();
+    walk(String::new(), value, &mut leaves);
+    leaves
+}
+
+fn diff(recorded: &BTreeMap<String, String>, actual: &BTreeMap<String, String>) -> Vec<String> {
+    let mut lines = Vec::new();
+    for (path, value) in recorded {
+        if actual.get(path) != Some(value) {
+            lines.push(format!("- {}: {}", path, value));
+        }
+        if let Some(actual_value) = actual.get(path) {
+            if actual_value != value {
+                lines.push(format!("+ {}: {}", path, actual_value));
+            }
+        }
+    }
+    for (path, value) in actual {
+        if !recorded.contains_key(path) {
+            lines.push(format!("+ {}: {}", path, value));
+        }
+    }
+    lines
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::assets::Assets;
+    use crate::auth::Auth;
+    use crate::environments::Environment;
+    use crate::institutions::Institutions;
+    use crate::items::Items;
+    use crate::plaid::Client;
+    use crate::transport::InMemoryTransport;
+    use std::env;
+
+    const REMOVE_ITEM: &str = r#"{"request_id": "m8MDnv9okwxFNBV", "removed": true}"#;
+
+    fn cassette_path(name: &str) -> PathBuf {
+        env::temp_dir().join(format!("plaid-rust-{}-{}.json", name, std::process::id()))
+    }
+
+    #[test]
+    fn test_record_and_replay() {
+        let path = cassette_path("record-and-replay");
+        let transport = InMemoryTransport::new().with_response("/item/remove", REMOVE_ITEM);
+        let recording = Client::new(
+            "client_id",
+            "d4e2a6c1f9",
+            "public_key",
+            Environment::SANDBOX,
+        )
+        .with_transport(transport)
+        .with_middleware(Cassette::record(&path));
+
+        recording.remove_item("access-sandbox-token").unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("d4e2a6c1f9"));
+        assert!(contents.contains(SCRUBBED));
+
+        let transport = InMemoryTransport::new();
+        let cassette = Cassette::replay(&path).unwrap();
+        let replaying = Client::new("other_id", "other_secret", "", Environment::SANDBOX)
+            .with_transport(transport.clone())
+            .with_middleware(cassette.clone());
+
+        let resp = replaying.remove_item("access-sandbox-token").unwrap();
+
+        assert!(cassette.is_replaying());
+        assert!(resp.removed);
+        assert!(transport.requests().is_empty());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_replay_mismatch() {
+        let path = cassette_path("replay-mismatch");
+        let transport = InMemoryTransport::new().with_response("/item/remove", REMOVE_ITEM);
+        let recording = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport)
+            .with_middleware(Cassette::record(&path));
+        recording.remove_item("access-sandbox-token").unwrap();
+
+        let replaying = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_middleware(Cassette::replay(&path).unwrap());
+
+        let err = replaying.remove_item("access-sandbox-other").err().unwrap();
+
+        let message = err.to_string();
+        assert!(message.contains(&format!(
+            r#"- access_token: "{}""#,
+            placeholder("access-sandbox-token")
+        )));
+        assert!(message.contains(&format!(
+            r#"+ access_token: "{}""#,
+            placeholder("access-sandbox-other")
+        )));
+        assert!(!message.contains("access-sandbox-other"));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_record_redacts_tokens_and_account_numbers() {
+        let path = cassette_path("redacts");
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/item/public_token/exchange",
+                include_str!("../fixtures/item/public_token/exchange.json"),
+            )
+            .with_response("/auth/get", include_str!("../fixtures/auth/get.json"));
+        let recording = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport)
+            .with_middleware(Cassette::record(&path));
+
+        let exchanged = recording
+            .exchange_public_token("public-sandbox-b0e2c4ee")
+            .unwrap();
+        recording.get_auth(&exchanged.access_token).unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains("public-sandbox-b0e2c4ee"));
+        assert!(!contents.contains("access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6"));
+        assert!(!contents.contains("1111222233330000"));
+        assert!(!contents.contains("011401533"));
+
+        let replaying = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_middleware(Cassette::replay(&path).unwrap());
+
+        let exchanged = replaying
+            .exchange_public_token("public-sandbox-b0e2c4ee")
+            .unwrap();
+        assert!(exchanged.access_token.starts_with(REDACTED_PREFIX));
+        let auth = replaying.get_auth(&exchanged.access_token).unwrap();
+        assert!(auth.numbers.ach[0].account.starts_with(REDACTED_PREFIX));
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_record_and_replay_institution() {
+        let path = cassette_path("institution");
+        let institutions: Value =
+            serde_json::from_str(include_str!("../fixtures/institutions/get.json")).unwrap();
+        let body = serde_json::json!({
+            "institution": institutions["institutions"][0],
+            "request_id": "m8MDnv9okwxFNBV"
+        });
+        let transport =
+            InMemoryTransport::new().with_response("/institutions/get_by_id", &body.to_string());
+        let recording = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport)
+            .with_middleware(Cassette::record(&path));
+        recording.get_institution_by_id("ins_109508").unwrap();
+
+        let contents = fs::read_to_string(&path).unwrap();
+        assert!(!contents.contains(REDACTED_PREFIX));
+
+        let replaying = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_middleware(Cassette::replay(&path).unwrap());
+
+        let resp = replaying.get_institution_by_id("ins_109508").unwrap();
+        assert_eq!("ins_109508", resp.institution.institution_id);
+        assert_eq!("First Platypus Bank", resp.institution.name);
+        assert_eq!(9, resp.institution.products.len());
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_record_and_replay_binary_body() {
+        let path = cassette_path("binary-body");
+        let pdf = b"%PDF-1.4\n\xe2\xe3\xcf\xd3\n%%EOF".to_vec();
+        let transport = InMemoryTransport::new();
+        transport.push_response(
+            "/asset_report/pdf/get",
+            HttpResponse {
+                status: 200,
+                headers: vec![("Content-Type".to_string(), "application/pdf".to_string())],
+                body: pdf.clone(),
+            },
+        );
+        let recording = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport)
+            .with_middleware(Cassette::record(&path));
+
+        assert_eq!(
+            pdf,
+            recording
+                .get_asset_report_pdf("assets-sandbox-6f12f5bb")
+                .unwrap()
+        );
+
+        let replaying = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_middleware(Cassette::replay(&path).unwrap());
+
+        assert_eq!(
+            pdf,
+            replaying
+                .get_asset_report_pdf("assets-sandbox-6f12f5bb")
+                .unwrap()
+        );
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn test_normalize_body() {
+        assert_eq!(
+            normalize_body(r#"{"secret": "a", "b": 1, "a": [2]}"#).to_string(),
+            normalize_body(r#"{"a": [2], "secret": "c", "b": 1}"#).to_string()
+        );
+    }
+}
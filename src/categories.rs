@@ -1,11 +1,23 @@
+use crate::endpoint::{Credentials, Endpoint};
 use crate::errors::Error;
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{AsyncClient, ResponseFuture};
 use serde::*;
 
-trait Categories {
+pub trait Categories {
     fn get_categories(&self) -> Result<GetCategoriesResponse, Error>;
 }
 
+#[derive(Serialize)]
+struct GetCategoriesRequest {}
+
+impl Endpoint for GetCategoriesRequest {
+    type Response = GetCategoriesResponse;
+    const PATH: &'static str = "/categories/get";
+    const CREDENTIALS: Credentials = Credentials::None;
+}
+
 #[derive(Deserialize)]
 pub struct Category {
     pub category_id: String,
@@ -19,9 +31,40 @@ pub struct GetCategoriesResponse {
     pub categories: Vec<Category>,
 }
 
-impl<'a> Categories for Client<'a> {
+impl Categories for Client {
     fn get_categories(&self) -> Result<GetCategoriesResponse, Error> {
-        self.call("/categories/get", "null")
+        self.send(&GetCategoriesRequest {})
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncCategories {
+    fn get_categories(&self) -> ResponseFuture<GetCategoriesResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncCategories for AsyncClient {
+    fn get_categories(&self) -> ResponseFuture<GetCategoriesResponse> {
+        self.send(&GetCategoriesRequest {})
+    }
+}
+
+/// CategoriesApi groups the categories endpoints, e.g.
+/// `client.categories().get(..)`.
+pub struct CategoriesApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl CategoriesApi<'_, Client> {
+    pub fn get(&self) -> Result<GetCategoriesResponse, Error> {
+        self.client.get_categories()
+    }
+}
+
+#[cfg(feature = "async")]
+impl CategoriesApi<'_, AsyncClient> {
+    pub fn get(&self) -> ResponseFuture<GetCategoriesResponse> {
+        self.client.get_categories()
     }
 }
 
@@ -29,20 +72,16 @@ impl<'a> Categories for Client<'a> {
 mod tests {
     use super::*;
     use crate::environments::Environment;
-    use std::env;
+    use crate::transport::InMemoryTransport;
 
     #[test]
     fn test_get_categories() {
-        let client_id = env::var("PLAID_CLIENT_ID").unwrap();
-        let secret = env::var("PLAID_SECRET").unwrap();
-        let public_key = env::var("PLAID_PUBLIC_KEY").unwrap();
-        let test_client = Client {
-            client_id: client_id.as_str(),
-            secret: secret.as_str(),
-            public_key: public_key.as_str(),
-            environment: Environment::SANDBOX,
-            http_client: reqwest::Client::new(),
-        };
+        let transport = InMemoryTransport::new().with_response(
+            "/categories/get",
+            include_str!("../fixtures/categories/get.json"),
+        );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport);
 
         let categories_resp = test_client.get_categories().unwrap();
 
@@ -0,0 +1,45 @@
+use crate::errors::Error;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+/// CursorStore persists how far `/transactions/sync` has got for every item,
+/// so a sync worker can pick up where it left off after a restart. Cursors are
+/// keyed by item ID.
+///
+/// Implement it over your own database; failures can be reported with
+/// `Error::cursor_store`.
+pub trait CursorStore: Send + Sync {
+    /// Returns the cursor saved for `item_id`, or `None` if the item has
+    /// never been synced.
+    fn load(&self, item_id: &str) -> Result<Option<String>, Error>;
+
+    /// Saves the cursor to resume syncing `item_id` from.
+    fn save(&self, item_id: &str, cursor: &str) -> Result<(), Error>;
+}
+
+/// InMemoryCursorStore keeps cursors in memory, for tests and short-lived
+/// workers. Clones share their cursors.
+#[derive(Clone, Default)]
+pub struct InMemoryCursorStore {
+    cursors: Arc<Mutex<HashMap<String, String>>>,
+}
+
+impl InMemoryCursorStore {
+    pub fn new() -> InMemoryCursorStore {
+        InMemoryCursorStore::default()
+    }
+}
+
+impl CursorStore for InMemoryCursorStore {
+    fn load(&self, item_id: &str) -> Result<Option<String>, Error> {
+        Ok(self.cursors.lock().unwrap().get(item_id).cloned())
+    }
+
+    fn save(&self, item_id: &str, cursor: &str) -> Result<(), Error> {
+        self.cursors
+            .lock()
+            .unwrap()
+            .insert(item_id.to_string(), cursor.to_string());
+        Ok(())
+    }
+}
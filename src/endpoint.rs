@@ -0,0 +1,157 @@
+use crate::errors::{Error, Kind};
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Endpoint describes a Plaid endpoint by its request type: where requests
+/// are posted, which credentials they carry and what the response decodes to.
+/// Requests are sent with `Client::send`, which adds the credentials, so
+/// request types only hold the endpoint's own fields.
+///
+/// Every JSON endpoint the crate wraps is built on it, and endpoints it does
+/// not wrap yet can be called by implementing it for a request type of your
+/// own:
+///
+/// `impl Endpoint for GetWidgetsRequest { type Response = GetWidgetsResponse;
+/// const PATH: &'static str = "/widgets/get"; }`
+pub trait Endpoint: Serialize {
+    type Response: DeserializeOwned + Send + 'static;
+
+    /// The path requests are posted to, e.g. `/item/get`.
+    const PATH: &'static str;
+
+    /// The credentials added to every request.
+    const CREDENTIALS: Credentials = Credentials::ClientSecret;
+}
+
+/// RawEndpoint describes an endpoint whose successful responses are not
+/// JSON, such as the PDF served by `/asset_report/pdf/get`. Requests are sent
+/// with `Client::send_raw`, which returns the body as is, or with
+/// `Client::write_raw`, which copies it into a writer. Error responses are
+/// still decoded as API errors.
+pub trait RawEndpoint: Serialize {
+    /// The path requests are posted to.
+    const PATH: &'static str;
+
+    /// The credentials added to every request.
+    const CREDENTIALS: Credentials = Credentials::ClientSecret;
+}
+
+/// Credentials lists which of the client's credentials an endpoint expects in
+/// its request body.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Credentials {
+    /// `client_id` and `secret`, used by almost every endpoint.
+    ClientSecret,
+    /// `public_key`, used by the endpoints Plaid Link can call.
+    PublicKey,
+    /// Nothing, for public endpoints such as `/categories/get`.
+    None,
+}
+
+#[derive(Serialize)]
+struct Authenticated<'a, E> {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    client_id: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    secret: Option<&'a str>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    public_key: Option<&'a str>,
+    #[serde(flatten)]
+    request: &'a E,
+}
+
+// encode serializes `request` along with the credentials its endpoint
+// expects.
+pub(crate) fn encode<E: Endpoint>(
+    request: &E,
+    client_id: &str,
+    secret: &str,
+    public_key: &str,
+) -> Result<String, Error> {
+    encode_with(request, E::CREDENTIALS, client_id, secret, public_key)
+}
+
+// encode_raw is `encode` for raw endpoints.
+pub(crate) fn encode_raw<E: RawEndpoint>(
+    request: &E,
+    client_id: &str,
+    secret: &str,
+    public_key: &str,
+) -> Result<String, Error> {
+    encode_with(request, E::CREDENTIALS, client_id, secret, public_key)
+}
+
+fn encode_with<R: Serialize>(
+    request: &R,
+    credentials: Credentials,
+    client_id: &str,
+    secret: &str,
+    public_key: &str,
+) -> Result<String, Error> {
+    let (client_id, secret, public_key) = match credentials {
+        Credentials::ClientSecret => (Some(client_id), Some(secret), None),
+        Credentials::PublicKey => (None, None, Some(public_key)),
+        Credentials::None => (None, None, None),
+    };
+
+    serde_json::to_string(&Authenticated {
+        client_id,
+        secret,
+        public_key,
+        request,
+    })
+    .map_err(|err| Error::new(Kind::Json(err)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::plaid::Client;
+    use crate::transport::InMemoryTransport;
+    use serde::Deserialize;
+    use serde_json::Value;
+
+    #[derive(Serialize)]
+    struct GetWidgetsRequest<'a> {
+        access_token: &'a str,
+        count: u32,
+    }
+
+    #[derive(Deserialize)]
+    struct GetWidgetsResponse {
+        widgets: Vec<String>,
+    }
+
+    impl Endpoint for GetWidgetsRequest<'_> {
+        type Response = GetWidgetsResponse;
+        const PATH: &'static str = "/widgets/get";
+    }
+
+    #[test]
+    fn test_send() {
+        let transport = InMemoryTransport::new()
+            .with_response("/widgets/get", r#"{"widgets": ["gear", "sprocket"]}"#);
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone());
+
+        let resp = test_client
+            .send(&GetWidgetsRequest {
+                access_token: "access-sandbox-token",
+                count: 2,
+            })
+            .unwrap();
+
+        assert_eq!(vec!["gear", "sprocket"], resp.widgets);
+        let body: Value = serde_json::from_str(&transport.requests()[0].body).unwrap();
+        assert_eq!(
+            serde_json::json!({
+                "client_id": "client_id",
+                "secret": "secret",
+                "access_token": "access-sandbox-token",
+                "count": 2,
+            }),
+            body
+        );
+    }
+}
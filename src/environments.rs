@@ -1,15 +1,114 @@
+use crate::errors::{Error, Kind};
+use std::fmt;
+use std::str::FromStr;
+
+/// Environment selects which Plaid deployment the client talks to. `CUSTOM`
+/// holds a full base URL, e.g. `http://localhost:8080`, for pointing the
+/// client at a local stand-in for Plaid or an egress proxy.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub enum Environment {
     SANDBOX,
     DEVELOPMENT,
     PRODUCTION,
+    CUSTOM(String),
 }
 
 impl Environment {
+    /// Returns the host of the environment, without scheme or trailing slash.
     pub fn host(&self) -> &str {
         match self {
             Environment::SANDBOX => "sandbox.plaid.com",
             Environment::DEVELOPMENT => "development.plaid.com",
             Environment::PRODUCTION => "production.plaid.com",
+            Environment::CUSTOM(base_url) => {
+                let host = base_url.trim_end_matches('/');
+                host.split_once("://").map_or(host, |(_, host)| host)
+            }
+        }
+    }
+
+    /// Returns the URL every endpoint path is appended to.
+    pub fn base_url(&self) -> String {
+        match self {
+            Environment::CUSTOM(base_url) => base_url.trim_end_matches('/').to_string(),
+            _ => format!("https://{}", self.host()),
+        }
+    }
+}
+
+impl FromStr for Environment {
+    type Err = Error;
+
+    /// Parses `sandbox`, `development` or `production` in any case, or an
+    /// `http://` or `https://` URL as a custom environment.
+    fn from_str(s: &str) -> Result<Environment, Error> {
+        let s = s.trim();
+        match s.to_lowercase().as_str() {
+            "sandbox" => Ok(Environment::SANDBOX),
+            "development" => Ok(Environment::DEVELOPMENT),
+            "production" => Ok(Environment::PRODUCTION),
+            lower if lower.starts_with("http://") || lower.starts_with("https://") => {
+                Ok(Environment::CUSTOM(s.to_string()))
+            }
+            _ => Err(Error::new(Kind::UnknownEnvironment(s.to_string()))),
+        }
+    }
+}
+
+impl fmt::Display for Environment {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Environment::SANDBOX => f.write_str("sandbox"),
+            Environment::DEVELOPMENT => f.write_str("development"),
+            Environment::PRODUCTION => f.write_str("production"),
+            Environment::CUSTOM(base_url) => f.write_str(base_url),
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::Items;
+    use crate::plaid::Client;
+    use crate::transport::InMemoryTransport;
+
+    #[test]
+    fn test_parse() {
+        assert_eq!(Environment::SANDBOX, "sandbox".parse().unwrap());
+        assert_eq!(Environment::DEVELOPMENT, "Development".parse().unwrap());
+        assert_eq!(Environment::PRODUCTION, " PRODUCTION ".parse().unwrap());
+        assert_eq!(
+            Environment::CUSTOM("http://localhost:8080".to_string()),
+            "http://localhost:8080".parse().unwrap()
+        );
+        assert!("staging".parse::<Environment>().is_err());
+    }
+
+    #[test]
+    fn test_base_url() {
+        assert_eq!("https://sandbox.plaid.com", Environment::SANDBOX.base_url());
+
+        let custom = Environment::CUSTOM("http://localhost:8080/".to_string());
+        assert_eq!("http://localhost:8080", custom.base_url());
+        assert_eq!("localhost:8080", custom.host());
+    }
+
+    #[test]
+    fn test_client_uses_custom_base_url() {
+        let transport = InMemoryTransport::new().with_response(
+            "/item/remove",
+            r#"{"request_id": "m8MDnv9okwxFNBV", "removed": true}"#,
+        );
+        let environment = "http://localhost:8080/".parse().unwrap();
+        let test_client = Client::new("client_id", "secret", "public_key", environment)
+            .with_transport(transport.clone());
+
+        test_client.remove_item("access-sandbox-token").unwrap();
+
+        assert_eq!(
+            "http://localhost:8080/item/remove",
+            transport.requests()[0].url
+        );
+    }
+}
@@ -1,3 +1,8 @@
+use crate::logging::redact;
+use serde::Deserialize;
+use std::error::Error as StdError;
+use std::fmt;
+
 #[derive(Debug)]
 pub struct Error {
     inner: Box<Inner>,
@@ -6,7 +11,148 @@ pub struct Error {
 impl Error {
     pub(crate) fn new(kind: Kind) -> Error {
         Error {
-            inner: Box::new(Inner { kind }),
+            inner: Box::new(Inner { kind, attempts: 1 }),
+        }
+    }
+
+    /// Wraps an error raised by a `CursorStore`, e.g. by the database it
+    /// keeps cursors in.
+    pub fn cursor_store<E>(err: E) -> Error
+    where
+        E: Into<Box<dyn StdError + Send + Sync>>,
+    {
+        Error::new(Kind::CursorStore(err.into()))
+    }
+
+    pub(crate) fn with_attempts(mut self, attempts: u32) -> Error {
+        self.inner.attempts = attempts;
+        self
+    }
+
+    /// Returns how many times the call was attempted before giving up.
+    pub fn attempts(&self) -> u32 {
+        self.inner.attempts
+    }
+
+    /// Returns the error reported by Plaid, if the API rejected the request.
+    pub fn api_error(&self) -> Option<&ApiError> {
+        match self.inner.kind {
+            Kind::Api(ref err) | Kind::NotReady(_, Some(ref err)) => Some(err),
+            _ => None,
+        }
+    }
+
+    /// Returns the `error_type` reported by Plaid, if any.
+    pub fn error_type(&self) -> Option<&ErrorType> {
+        self.api_error().map(|err| &err.error_type)
+    }
+
+    /// Returns the `error_code` reported by Plaid, if any.
+    pub fn error_code(&self) -> Option<&ErrorCode> {
+        self.api_error().map(|err| &err.error_code)
+    }
+
+    /// Returns the `request_id` Plaid assigned to the failed request, if any.
+    pub fn request_id(&self) -> Option<&str> {
+        self.api_error().and_then(|err| err.request_id.as_deref())
+    }
+
+    /// Returns the HTTP status of the response that caused this error, if a
+    /// response was received.
+    pub fn status(&self) -> Option<u16> {
+        match self.inner.kind {
+            Kind::Api(ref err) | Kind::NotReady(_, Some(ref err)) => Some(err.status),
+            Kind::Status(status, _) => Some(status),
+            Kind::Reqwest(ref err) => err.status().map(|status| status.as_u16()),
+            _ => None,
+        }
+    }
+
+    /// Returns true if Plaid answered the request with an error body.
+    pub fn is_api(&self) -> bool {
+        self.api_error().is_some()
+    }
+
+    /// Returns true if the request failed before a response was received.
+    pub fn is_network(&self) -> bool {
+        match self.inner.kind {
+            Kind::Reqwest(ref err) => err.status().is_none() && !err.is_serialization(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if Plaid rejected the request for exceeding a rate limit.
+    pub fn is_rate_limited(&self) -> bool {
+        self.error_type() == Some(&ErrorType::RateLimitExceeded) || self.status() == Some(429)
+    }
+
+    /// Returns true if an attempt at the call ran out of time. Waits given up
+    /// by the polling helpers are reported by `is_not_ready` instead.
+    pub fn is_timeout(&self) -> bool {
+        match self.inner.kind {
+            Kind::Timeout(_) => true,
+            Kind::Reqwest(ref err) => err.is_timeout(),
+            _ => false,
+        }
+    }
+
+    /// Returns true if a polling helper gave up once its deadline passed: the
+    /// product was still not ready, in which case `api_error` returns the last
+    /// `PRODUCT_NOT_READY` error Plaid reported, or the update waited for was
+    /// never reported.
+    pub fn is_not_ready(&self) -> bool {
+        matches!(self.inner.kind, Kind::NotReady(..))
+    }
+
+    /// Returns true if the call was cancelled before it completed.
+    #[cfg(feature = "async")]
+    pub fn is_cancelled(&self) -> bool {
+        matches!(self.inner.kind, Kind::Cancelled)
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.inner.kind {
+            Kind::Reqwest(ref err) => write!(f, "http error: {}", err),
+            Kind::Json(ref err) => write!(f, "json error: {}", err),
+            Kind::Transport(ref msg) => write!(f, "transport error: {}", msg),
+            Kind::Timeout(timeout) => write!(f, "request timed out after {:?}", timeout),
+            Kind::NotReady(waited, Some(ref err)) => {
+                write!(f, "product not ready after {:?}: {}", waited, err)
+            }
+            Kind::NotReady(waited, None) => {
+                write!(f, "product not updated after {:?}", waited)
+            }
+            #[cfg(feature = "async")]
+            Kind::Cancelled => f.write_str("request was cancelled"),
+            Kind::Io(ref err) => write!(f, "io error: {}", err),
+            Kind::Cassette(ref msg) => write!(f, "cassette error: {}", msg),
+            Kind::CursorStore(ref err) => write!(f, "cursor store error: {}", err),
+            Kind::NotRemoved(what) => write!(f, "plaid did not remove the {}", what),
+            Kind::Api(ref err) => write!(f, "api error: {}", err),
+            // The body is redacted, since errors are commonly logged as is.
+            Kind::Status(status, ref body) => {
+                write!(f, "unexpected http status {}: {}", status, redact(body))
+            }
+            Kind::EmptyId => f.write_str("id must be specified"),
+            Kind::EmptyQuery => f.write_str("query must be specified"),
+            Kind::EmptyToken => f.write_str("token must be specified"),
+            Kind::ValidationError(msg) => f.write_str(msg),
+            Kind::UnknownEnvironment(ref name) => write!(f, "unknown environment: {}", name),
+        }
+    }
+}
+
+impl StdError for Error {
+    fn source(&self) -> Option<&(dyn StdError + 'static)> {
+        match self.inner.kind {
+            Kind::Reqwest(ref err) => Some(err),
+            Kind::Json(ref err) => Some(err),
+            Kind::Io(ref err) => Some(err),
+            Kind::CursorStore(ref err) => Some(err.as_ref()),
+            Kind::Api(ref err) | Kind::NotReady(_, Some(ref err)) => Some(err),
+            _ => None,
         }
     }
 }
@@ -14,15 +160,281 @@ impl Error {
 #[derive(Debug)]
 struct Inner {
     kind: Kind,
+    attempts: u32,
 }
 
 #[derive(Debug)]
 pub(crate) enum Kind {
     Reqwest(::reqwest::Error),
     Json(::serde_json::Error),
+    Transport(String),
+    Timeout(::std::time::Duration),
+    NotReady(::std::time::Duration, Option<ApiError>),
+    #[cfg(feature = "async")]
+    Cancelled,
+    Io(::std::io::Error),
+    Cassette(String),
+    CursorStore(Box<dyn StdError + Send + Sync>),
+    NotRemoved(&'static str),
+    Api(ApiError),
+    Status(u16, String),
 
     EmptyId,
     EmptyQuery,
     EmptyToken,
     ValidationError(&'static str),
+    UnknownEnvironment(String),
+}
+
+/// ApiError is the error body Plaid returns alongside a non-2xx status.
+#[derive(Clone, Debug, Deserialize)]
+pub struct ApiError {
+    #[serde(skip)]
+    pub status: u16,
+    pub error_type: ErrorType,
+    pub error_code: ErrorCode,
+    pub error_message: String,
+    pub display_message: Option<String>,
+    pub request_id: Option<String>,
+}
+
+impl fmt::Display for ApiError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "{} {}: {}",
+            self.error_type, self.error_code, self.error_message
+        )
+    }
+}
+
+impl StdError for ApiError {}
+
+// string_enum declares an enum of Plaid constants that round-trips through its
+// wire representation, falling back to `Unknown` for values this crate does
+// not know about yet.
+macro_rules! string_enum {
+    ($(#[$meta:meta])* pub enum $name:ident { $($variant:ident => $value:expr,)* }) => {
+        $(#[$meta])*
+        #[derive(Clone, Debug, PartialEq, Eq, Hash, Deserialize)]
+        #[serde(from = "String")]
+        pub enum $name {
+            $($variant,)*
+            Unknown(String),
+        }
+
+        impl $name {
+            pub fn as_str(&self) -> &str {
+                match *self {
+                    $($name::$variant => $value,)*
+                    $name::Unknown(ref value) => value.as_str(),
+                }
+            }
+        }
+
+        impl<'a> From<&'a str> for $name {
+            fn from(value: &'a str) -> $name {
+                match value {
+                    $($value => $name::$variant,)*
+                    _ => $name::Unknown(value.to_string()),
+                }
+            }
+        }
+
+        impl From<String> for $name {
+            fn from(value: String) -> $name {
+                $name::from(value.as_str())
+            }
+        }
+
+        impl fmt::Display for $name {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                f.write_str(self.as_str())
+            }
+        }
+    };
+}
+
+string_enum! {
+    /// ErrorType is the broad category of a Plaid error.
+    pub enum ErrorType {
+        InvalidRequest => "INVALID_REQUEST",
+        InvalidInput => "INVALID_INPUT",
+        InstitutionError => "INSTITUTION_ERROR",
+        RateLimitExceeded => "RATE_LIMIT_EXCEEDED",
+        ApiError => "API_ERROR",
+        ItemError => "ITEM_ERROR",
+        AssetReportError => "ASSET_REPORT_ERROR",
+        RecaptchaError => "RECAPTCHA_ERROR",
+        TransactionsError => "TRANSACTIONS_ERROR",
+    }
+}
+
+string_enum! {
+    /// ErrorCode is the specific reason Plaid rejected a request.
+    pub enum ErrorCode {
+        // INVALID_REQUEST
+        MissingFields => "MISSING_FIELDS",
+        UnknownFields => "UNKNOWN_FIELDS",
+        InvalidField => "INVALID_FIELD",
+        InvalidBody => "INVALID_BODY",
+        InvalidHeaders => "INVALID_HEADERS",
+        NotFound => "NOT_FOUND",
+        SandboxOnly => "SANDBOX_ONLY",
+
+        // INVALID_INPUT
+        InvalidApiKeys => "INVALID_API_KEYS",
+        UnauthorizedEnvironment => "UNAUTHORIZED_ENVIRONMENT",
+        InvalidAccessToken => "INVALID_ACCESS_TOKEN",
+        InvalidPublicToken => "INVALID_PUBLIC_TOKEN",
+        InvalidProduct => "INVALID_PRODUCT",
+        InvalidAccountId => "INVALID_ACCOUNT_ID",
+        InvalidInstitution => "INVALID_INSTITUTION",
+        TooManyVerificationAttempts => "TOO_MANY_VERIFICATION_ATTEMPTS",
+
+        // INSTITUTION_ERROR
+        InstitutionDown => "INSTITUTION_DOWN",
+        InstitutionNotResponding => "INSTITUTION_NOT_RESPONDING",
+        InstitutionNotAvailable => "INSTITUTION_NOT_AVAILABLE",
+        InstitutionNoLongerSupported => "INSTITUTION_NO_LONGER_SUPPORTED",
+
+        // RATE_LIMIT_EXCEEDED
+        AccountsLimit => "ACCOUNTS_LIMIT",
+        AdditionLimit => "ADDITION_LIMIT",
+        AuthLimit => "AUTH_LIMIT",
+        BalanceLimit => "BALANCE_LIMIT",
+        IdentityLimit => "IDENTITY_LIMIT",
+        IncomeLimit => "INCOME_LIMIT",
+        ItemGetLimit => "ITEM_GET_LIMIT",
+        RateLimit => "RATE_LIMIT",
+        RateLimitExceeded => "RATE_LIMIT_EXCEEDED",
+        TransactionsLimit => "TRANSACTIONS_LIMIT",
+
+        // API_ERROR
+        InternalServerError => "INTERNAL_SERVER_ERROR",
+        PlannedMaintenance => "PLANNED_MAINTENANCE",
+
+        // ITEM_ERROR
+        InvalidCredentials => "INVALID_CREDENTIALS",
+        InvalidMfa => "INVALID_MFA",
+        InvalidSendMethod => "INVALID_SEND_METHOD",
+        InvalidUpdatedUsername => "INVALID_UPDATED_USERNAME",
+        ItemLocked => "ITEM_LOCKED",
+        ItemLoginRequired => "ITEM_LOGIN_REQUIRED",
+        ItemNoError => "ITEM_NO_ERROR",
+        ItemNotSupported => "ITEM_NOT_SUPPORTED",
+        IncorrectDepositAmounts => "INCORRECT_DEPOSIT_AMOUNTS",
+        UserSetupRequired => "USER_SETUP_REQUIRED",
+        MfaNotSupported => "MFA_NOT_SUPPORTED",
+        NoAccounts => "NO_ACCOUNTS",
+        NoAuthAccounts => "NO_AUTH_ACCOUNTS",
+        NoInvestmentAccounts => "NO_INVESTMENT_ACCOUNTS",
+        NoLiabilityAccounts => "NO_LIABILITY_ACCOUNTS",
+        ProductNotReady => "PRODUCT_NOT_READY",
+        ProductsNotSupported => "PRODUCTS_NOT_SUPPORTED",
+
+        // ASSET_REPORT_ERROR
+        ProductNotEnabled => "PRODUCT_NOT_ENABLED",
+        DataUnavailable => "DATA_UNAVAILABLE",
+        AssetReportGenerationFailed => "ASSET_REPORT_GENERATION_FAILED",
+        InvalidParent => "INVALID_PARENT",
+        InsightsNotEnabled => "INSIGHTS_NOT_ENABLED",
+        InsightsPreviouslyNotEnabled => "INSIGHTS_PREVIOUSLY_NOT_ENABLED",
+
+        // RECAPTCHA_ERROR
+        RecaptchaRequired => "RECAPTCHA_REQUIRED",
+        RecaptchaBad => "RECAPTCHA_BAD",
+
+        // TRANSACTIONS_ERROR
+        TransactionsSyncMutationDuringPagination => "TRANSACTIONS_SYNC_MUTATION_DURING_PAGINATION",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::items::Items;
+    use crate::plaid::Client;
+    use crate::transport::InMemoryTransport;
+
+    #[test]
+    fn test_api_error() {
+        let transport = InMemoryTransport::new().with_status_response(
+            "/item/get",
+            400,
+            r#"{
+                "display_message": null,
+                "error_code": "ITEM_LOGIN_REQUIRED",
+                "error_message": "the login details of this item have changed",
+                "error_type": "ITEM_ERROR",
+                "request_id": "HNTDNrA8F1shFEW"
+            }"#,
+        );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport);
+
+        let err = test_client.get_item("access-sandbox-token").err().unwrap();
+
+        assert!(err.is_api());
+        assert_eq!(Some(400), err.status());
+        assert_eq!(Some(&ErrorType::ItemError), err.error_type());
+        assert_eq!(Some(&ErrorCode::ItemLoginRequired), err.error_code());
+        assert_eq!(Some("HNTDNrA8F1shFEW"), err.request_id());
+    }
+
+    #[test]
+    fn test_unknown_error_code() {
+        let transport = InMemoryTransport::new().with_status_response(
+            "/item/get",
+            400,
+            r#"{
+                "display_message": null,
+                "error_code": "SOMETHING_NEW",
+                "error_message": "a brand new error",
+                "error_type": "NEW_ERROR_TYPE",
+                "request_id": "HNTDNrA8F1shFEW"
+            }"#,
+        );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport);
+
+        let err = test_client.get_item("access-sandbox-token").err().unwrap();
+
+        assert_eq!(
+            Some(&ErrorType::Unknown("NEW_ERROR_TYPE".to_string())),
+            err.error_type()
+        );
+        assert_eq!("SOMETHING_NEW", err.error_code().unwrap().as_str());
+    }
+
+    #[test]
+    fn test_unexpected_status() {
+        let transport =
+            InMemoryTransport::new().with_status_response("/item/get", 502, "Bad Gateway");
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport);
+
+        let err = test_client.get_item("access-sandbox-token").err().unwrap();
+
+        assert!(!err.is_api());
+        assert_eq!(Some(502), err.status());
+        assert_eq!(
+            "unexpected http status 502: [11 bytes redacted]",
+            err.to_string()
+        );
+    }
+
+    #[test]
+    fn test_unexpected_status_redacts_body() {
+        let err = Error::new(Kind::Status(
+            500,
+            r#"{"access_token": "access-sandbox-de3ce8ef", "item_id": "Ed6bjNrDLJfGvZWwnkQlfxwoNz54B5C97ejBr"}"#
+                .to_string(),
+        ));
+
+        let message = err.to_string();
+        assert!(!message.contains("access-sandbox-de3ce8ef"));
+        assert!(message.contains("Ed6bjNrDLJfGvZWwnkQlfxwoNz54B5C97ejBr"));
+    }
 }
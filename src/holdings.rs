@@ -1,10 +1,13 @@
 use crate::accounts::Account;
+use crate::endpoint::Endpoint;
 use crate::errors::{Error, Kind};
 use crate::items::Item;
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
 use serde::*;
 
-trait Holdings {
+pub trait Holdings {
     fn get_holdings(&self, access_token: &str) -> Result<GetHoldingsResponse, Error>;
     fn get_holdings_with_options(
         &self,
@@ -48,19 +51,34 @@ pub struct Holding {
 
 #[derive(Serialize)]
 struct GetHoldingsRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<GetHoldingsRequestOptions<'a>>,
 }
 
-#[derive(Serialize)]
+impl Endpoint for GetHoldingsRequest<'_> {
+    type Response = GetHoldingsResponse;
+    const PATH: &'static str = "/investments/holdings/get";
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct GetHoldingsRequestOptions<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     account_ids: Option<Vec<&'a str>>,
 }
 
+impl<'a> GetHoldingsRequestOptions<'a> {
+    pub fn new() -> GetHoldingsRequestOptions<'a> {
+        GetHoldingsRequestOptions::default()
+    }
+
+    /// Only returns data for the accounts with these IDs.
+    pub fn with_account_ids(mut self, account_ids: Vec<&'a str>) -> GetHoldingsRequestOptions<'a> {
+        self.account_ids = Some(account_ids);
+        self
+    }
+}
+
 #[derive(Deserialize)]
 pub struct GetHoldingsResponse {
     pub request_id: String,
@@ -70,7 +88,7 @@ pub struct GetHoldingsResponse {
     pub holdings: Vec<Holding>,
 }
 
-impl<'a> Holdings for Client<'a> {
+impl Holdings for Client {
     fn get_holdings(&self, access_token: &str) -> Result<GetHoldingsResponse, Error> {
         self.get_holdings_with_options(access_token, None)
     }
@@ -80,20 +98,85 @@ impl<'a> Holdings for Client<'a> {
         access_token: &str,
         options: Option<GetHoldingsRequestOptions>,
     ) -> Result<GetHoldingsResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             Err(Error::new(Kind::EmptyToken))?
         }
 
         let req = GetHoldingsRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/investments/holdings/get", &json_body))
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncHoldings {
+    fn get_holdings(&self, access_token: &str) -> ResponseFuture<GetHoldingsResponse>;
+    fn get_holdings_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetHoldingsRequestOptions>,
+    ) -> ResponseFuture<GetHoldingsResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncHoldings for AsyncClient {
+    fn get_holdings(&self, access_token: &str) -> ResponseFuture<GetHoldingsResponse> {
+        self.get_holdings_with_options(access_token, None)
+    }
+
+    fn get_holdings_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetHoldingsRequestOptions>,
+    ) -> ResponseFuture<GetHoldingsResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetHoldingsRequest {
+            access_token,
+            options,
+        };
+
+        self.send(&req)
+    }
+}
+
+/// HoldingsApi groups the investment holdings endpoints, e.g.
+/// `client.holdings().get(..)`.
+pub struct HoldingsApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl HoldingsApi<'_, Client> {
+    pub fn get(&self, access_token: &str) -> Result<GetHoldingsResponse, Error> {
+        self.client.get_holdings(access_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetHoldingsRequestOptions>,
+    ) -> Result<GetHoldingsResponse, Error> {
+        self.client.get_holdings_with_options(access_token, options)
+    }
+}
+
+#[cfg(feature = "async")]
+impl HoldingsApi<'_, AsyncClient> {
+    pub fn get(&self, access_token: &str) -> ResponseFuture<GetHoldingsResponse> {
+        self.client.get_holdings(access_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetHoldingsRequestOptions>,
+    ) -> ResponseFuture<GetHoldingsResponse> {
+        self.client.get_holdings_with_options(access_token, options)
     }
 }
 
@@ -103,20 +186,29 @@ mod tests {
     use crate::environments::Environment;
     use crate::items::Items;
     use crate::sandbox::Sandbox;
-    use std::env;
+    use crate::transport::InMemoryTransport;
 
     #[test]
     fn test_get_holdings() {
-        let client_id = env::var("PLAID_CLIENT_ID").unwrap();
-        let secret = env::var("PLAID_SECRET").unwrap();
-        let public_key = env::var("PLAID_PUBLIC_KEY").unwrap();
-        let test_client = Client {
-            client_id: client_id.as_str(),
-            secret: secret.as_str(),
-            public_key: public_key.as_str(),
-            environment: Environment::SANDBOX,
-            http_client: reqwest::Client::new(),
-        };
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/sandbox/public_token/create",
+                include_str!("../fixtures/sandbox/public_token/create.json"),
+            )
+            .with_response(
+                "/item/public_token/exchange",
+                include_str!("../fixtures/item/public_token/exchange.json"),
+            )
+            .with_response(
+                "/investments/holdings/get",
+                include_str!("../fixtures/investments/holdings/get.json"),
+            )
+            .with_response(
+                "/investments/holdings/get",
+                include_str!("../fixtures/investments/holdings/get_filtered.json"),
+            );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport);
 
         let sandbox_resp = test_client
             .create_sandbox_public_token("ins_109508", &["investments"])
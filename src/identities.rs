@@ -1,10 +1,13 @@
 use crate::accounts::Account;
+use crate::endpoint::Endpoint;
 use crate::errors::{Error, Kind};
 use crate::items::Item;
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
 use serde::*;
 
-trait Identities {
+pub trait Identities {
     fn get_identity(&self, access_token: &str) -> Result<GetIdentityResponse, Error>;
 }
 
@@ -49,11 +52,14 @@ pub struct PhoneNumber {
 
 #[derive(Serialize)]
 struct GetIdentityRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
 }
 
+impl Endpoint for GetIdentityRequest<'_> {
+    type Response = GetIdentityResponse;
+    const PATH: &'static str = "/identity/get";
+}
+
 #[derive(Deserialize)]
 pub struct AccountWithOwners {
     pub owners: Vec<Identity>,
@@ -68,21 +74,52 @@ pub struct GetIdentityResponse {
     pub item: Item,
 }
 
-impl<'a> Identities for Client<'a> {
+impl Identities for Client {
     fn get_identity(&self, access_token: &str) -> Result<GetIdentityResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             Err(Error::new(Kind::EmptyToken))?
         }
 
-        let req = GetIdentityRequest {
-            client_id: self.client_id,
-            secret: self.secret,
-            access_token,
-        };
+        let req = GetIdentityRequest { access_token };
+
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncIdentities {
+    fn get_identity(&self, access_token: &str) -> ResponseFuture<GetIdentityResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncIdentities for AsyncClient {
+    fn get_identity(&self, access_token: &str) -> ResponseFuture<GetIdentityResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetIdentityRequest { access_token };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/identity/get", &json_body))
+        self.send(&req)
+    }
+}
+
+/// IdentityApi groups the identity endpoints, e.g.
+/// `client.identity().get(..)`.
+pub struct IdentityApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl IdentityApi<'_, Client> {
+    pub fn get(&self, access_token: &str) -> Result<GetIdentityResponse, Error> {
+        self.client.get_identity(access_token)
+    }
+}
+
+#[cfg(feature = "async")]
+impl IdentityApi<'_, AsyncClient> {
+    pub fn get(&self, access_token: &str) -> ResponseFuture<GetIdentityResponse> {
+        self.client.get_identity(access_token)
     }
 }
 
@@ -92,20 +129,25 @@ mod tests {
     use crate::environments::Environment;
     use crate::items::Items;
     use crate::sandbox::Sandbox;
-    use std::env;
+    use crate::transport::InMemoryTransport;
 
     #[test]
     fn test_get_identity() {
-        let client_id = env::var("PLAID_CLIENT_ID").unwrap();
-        let secret = env::var("PLAID_SECRET").unwrap();
-        let public_key = env::var("PLAID_PUBLIC_KEY").unwrap();
-        let test_client = Client {
-            client_id: client_id.as_str(),
-            secret: secret.as_str(),
-            public_key: public_key.as_str(),
-            environment: Environment::SANDBOX,
-            http_client: reqwest::Client::new(),
-        };
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/sandbox/public_token/create",
+                include_str!("../fixtures/sandbox/public_token/create.json"),
+            )
+            .with_response(
+                "/item/public_token/exchange",
+                include_str!("../fixtures/item/public_token/exchange.json"),
+            )
+            .with_response(
+                "/identity/get",
+                include_str!("../fixtures/identity/get.json"),
+            );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport);
 
         let sandbox_resp = test_client
             .create_sandbox_public_token(
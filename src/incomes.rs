@@ -1,8 +1,11 @@
+use crate::endpoint::Endpoint;
 use crate::errors::{Error, Kind};
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
 use serde::*;
 
-trait Incomes {
+pub trait Incomes {
     fn get_income(&self, access_token: &str) -> Result<GetIncomeResponse, Error>;
 }
 
@@ -27,32 +30,66 @@ pub struct IncomeStream {
 
 #[derive(Serialize)]
 struct GetIncomeRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
 }
 
+impl Endpoint for GetIncomeRequest<'_> {
+    type Response = GetIncomeResponse;
+    const PATH: &'static str = "/income/get";
+}
+
 #[derive(Deserialize)]
 pub struct GetIncomeResponse {
     pub request_id: String,
     pub income: Income,
 }
 
-impl<'a> Incomes for Client<'a> {
+impl Incomes for Client {
     fn get_income(&self, access_token: &str) -> Result<GetIncomeResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             Err(Error::new(Kind::EmptyToken))?
         }
 
-        let req = GetIncomeRequest {
-            client_id: self.client_id,
-            secret: self.secret,
-            access_token,
-        };
+        let req = GetIncomeRequest { access_token };
+
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncIncomes {
+    fn get_income(&self, access_token: &str) -> ResponseFuture<GetIncomeResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncIncomes for AsyncClient {
+    fn get_income(&self, access_token: &str) -> ResponseFuture<GetIncomeResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetIncomeRequest { access_token };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/income/get", &json_body))
+        self.send(&req)
+    }
+}
+
+/// IncomeApi groups the income endpoints, e.g.
+/// `client.income().get(..)`.
+pub struct IncomeApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl IncomeApi<'_, Client> {
+    pub fn get(&self, access_token: &str) -> Result<GetIncomeResponse, Error> {
+        self.client.get_income(access_token)
+    }
+}
+
+#[cfg(feature = "async")]
+impl IncomeApi<'_, AsyncClient> {
+    pub fn get(&self, access_token: &str) -> ResponseFuture<GetIncomeResponse> {
+        self.client.get_income(access_token)
     }
 }
 
@@ -62,20 +99,22 @@ mod tests {
     use crate::environments::Environment;
     use crate::items::Items;
     use crate::sandbox::Sandbox;
-    use std::env;
+    use crate::transport::InMemoryTransport;
 
     #[test]
     fn test_get_income() {
-        let client_id = env::var("PLAID_CLIENT_ID").unwrap();
-        let secret = env::var("PLAID_SECRET").unwrap();
-        let public_key = env::var("PLAID_PUBLIC_KEY").unwrap();
-        let test_client = Client {
-            client_id: client_id.as_str(),
-            secret: secret.as_str(),
-            public_key: public_key.as_str(),
-            environment: Environment::SANDBOX,
-            http_client: reqwest::Client::new(),
-        };
+        let transport = InMemoryTransport::new()
+            .with_response(
+                "/sandbox/public_token/create",
+                include_str!("../fixtures/sandbox/public_token/create.json"),
+            )
+            .with_response(
+                "/item/public_token/exchange",
+                include_str!("../fixtures/item/public_token/exchange.json"),
+            )
+            .with_response("/income/get", include_str!("../fixtures/income/get.json"));
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport);
 
         let sandbox_resp = test_client
             .create_sandbox_public_token(
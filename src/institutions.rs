@@ -1,7 +1,15 @@
+use crate::endpoint::{Credentials, Endpoint};
 use crate::errors::{Error, Kind};
+use crate::pagination::{Pager, MAX_PAGE_SIZE};
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
+use crate::rate_limit::{RateLimit, RateLimiter};
+use crate::retry::RetryPolicy;
 use chrono::{DateTime, Utc};
 use serde::*;
+use std::thread;
+use std::time::Duration;
 
 pub trait Institutions {
     fn get_institutions(&self, count: u16, offset: u32) -> Result<GetInstitutionsResponse, Error>;
@@ -74,15 +82,18 @@ pub struct InstitutionStatusBreakdown {
 
 #[derive(Serialize)]
 struct GetInstitutionsRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     count: u16,
     offset: u32,
     #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<GetInstitutionsRequestOptions<'a>>,
 }
 
-#[derive(Serialize)]
+impl Endpoint for GetInstitutionsRequest<'_> {
+    type Response = GetInstitutionsResponse;
+    const PATH: &'static str = "/institutions/get";
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct GetInstitutionsRequestOptions<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     products: Option<Vec<&'a str>>,
@@ -92,6 +103,36 @@ pub struct GetInstitutionsRequestOptions<'a> {
     country_codes: Option<Vec<&'a str>>,
 }
 
+impl<'a> GetInstitutionsRequestOptions<'a> {
+    pub fn new() -> GetInstitutionsRequestOptions<'a> {
+        GetInstitutionsRequestOptions::default()
+    }
+
+    /// Only returns institutions that support all of these products.
+    pub fn with_products(mut self, products: Vec<&'a str>) -> GetInstitutionsRequestOptions<'a> {
+        self.products = Some(products);
+        self
+    }
+
+    /// Includes the institution's URL, primary color and logo.
+    pub fn with_include_optional_metadata(
+        mut self,
+        include_optional_metadata: bool,
+    ) -> GetInstitutionsRequestOptions<'a> {
+        self.include_optional_metadata = Some(include_optional_metadata);
+        self
+    }
+
+    /// Only returns institutions in these countries.
+    pub fn with_country_codes(
+        mut self,
+        country_codes: Vec<&'a str>,
+    ) -> GetInstitutionsRequestOptions<'a> {
+        self.country_codes = Some(country_codes);
+        self
+    }
+}
+
 #[derive(Debug, Deserialize)]
 pub struct GetInstitutionsResponse {
     pub request_id: String,
@@ -102,12 +143,17 @@ pub struct GetInstitutionsResponse {
 #[derive(Serialize)]
 struct GetInstitutionByIdRequest<'a> {
     institution_id: &'a str,
-    public_key: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<GetInstitutionByIdRequestOptions>,
 }
 
-#[derive(Serialize)]
+impl Endpoint for GetInstitutionByIdRequest<'_> {
+    type Response = GetInstitutionByIdResponse;
+    const PATH: &'static str = "/institutions/get_by_id";
+    const CREDENTIALS: Credentials = Credentials::PublicKey;
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct GetInstitutionByIdRequestOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     include_optional_metadata: Option<bool>,
@@ -115,6 +161,27 @@ pub struct GetInstitutionByIdRequestOptions {
     include_status: Option<bool>,
 }
 
+impl GetInstitutionByIdRequestOptions {
+    pub fn new() -> GetInstitutionByIdRequestOptions {
+        GetInstitutionByIdRequestOptions::default()
+    }
+
+    /// Includes the institution's URL, primary color and logo.
+    pub fn with_include_optional_metadata(
+        mut self,
+        include_optional_metadata: bool,
+    ) -> GetInstitutionByIdRequestOptions {
+        self.include_optional_metadata = Some(include_optional_metadata);
+        self
+    }
+
+    /// Includes the institution's health status.
+    pub fn with_include_status(mut self, include_status: bool) -> GetInstitutionByIdRequestOptions {
+        self.include_status = Some(include_status);
+        self
+    }
+}
+
 #[derive(Deserialize)]
 pub struct GetInstitutionByIdResponse {
     pub request_id: String,
@@ -125,12 +192,17 @@ pub struct GetInstitutionByIdResponse {
 struct SearchInstitutionsRequest<'a> {
     query: &'a str,
     products: Vec<&'a str>,
-    public_key: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<SearchInstitutionsRequestOptions<'a>>,
 }
 
-#[derive(Serialize)]
+impl Endpoint for SearchInstitutionsRequest<'_> {
+    type Response = SearchInstitutionsResponse;
+    const PATH: &'static str = "/institutions/search";
+    const CREDENTIALS: Credentials = Credentials::PublicKey;
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct SearchInstitutionsRequestOptions<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     include_optional_metadata: Option<bool>,
@@ -138,13 +210,37 @@ pub struct SearchInstitutionsRequestOptions<'a> {
     country_codes: Option<Vec<&'a str>>,
 }
 
+impl<'a> SearchInstitutionsRequestOptions<'a> {
+    pub fn new() -> SearchInstitutionsRequestOptions<'a> {
+        SearchInstitutionsRequestOptions::default()
+    }
+
+    /// Includes the institution's URL, primary color and logo.
+    pub fn with_include_optional_metadata(
+        mut self,
+        include_optional_metadata: bool,
+    ) -> SearchInstitutionsRequestOptions<'a> {
+        self.include_optional_metadata = Some(include_optional_metadata);
+        self
+    }
+
+    /// Only returns institutions in these countries.
+    pub fn with_country_codes(
+        mut self,
+        country_codes: Vec<&'a str>,
+    ) -> SearchInstitutionsRequestOptions<'a> {
+        self.country_codes = Some(country_codes);
+        self
+    }
+}
+
 #[derive(Deserialize)]
 pub struct SearchInstitutionsResponse {
     pub request_id: String,
     pub institutions: Vec<Institution>,
 }
 
-impl<'a> Institutions for Client<'a> {
+impl Institutions for Client {
     fn get_institutions(&self, count: u16, offset: u32) -> Result<GetInstitutionsResponse, Error> {
         self.get_institutions_with_options(count, offset, None)
     }
@@ -161,16 +257,12 @@ impl<'a> Institutions for Client<'a> {
         }
 
         let req = GetInstitutionsRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             count: result_count,
             offset,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/institutions/get", &json_body))
+        self.send(&req)
     }
 
     fn search_institutions(
@@ -187,20 +279,17 @@ impl<'a> Institutions for Client<'a> {
         products: Vec<&str>,
         options: Option<SearchInstitutionsRequestOptions>,
     ) -> Result<SearchInstitutionsResponse, Error> {
-        if query == "" {
+        if query.is_empty() {
             return Err(Error::new(Kind::EmptyQuery));
         }
 
         let req = SearchInstitutionsRequest {
             query,
             products,
-            public_key: self.public_key,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/institutions/search", &json_body))
+        self.send(&req)
     }
 
     fn get_institution_by_id(&self, id: &str) -> Result<GetInstitutionByIdResponse, Error> {
@@ -212,18 +301,482 @@ impl<'a> Institutions for Client<'a> {
         id: &str,
         options: Option<GetInstitutionByIdRequestOptions>,
     ) -> Result<GetInstitutionByIdResponse, Error> {
-        if id == "" {
+        if id.is_empty() {
             return Err(Error::new(Kind::EmptyId));
         }
 
         let req = GetInstitutionByIdRequest {
             institution_id: id,
-            public_key: self.public_key,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/institutions/get_by_id", &json_body))
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncInstitutions {
+    fn get_institutions(&self, count: u16, offset: u32) -> ResponseFuture<GetInstitutionsResponse>;
+    fn get_institutions_with_options(
+        &self,
+        count: u16,
+        offset: u32,
+        options: Option<GetInstitutionsRequestOptions>,
+    ) -> ResponseFuture<GetInstitutionsResponse>;
+    fn search_institutions(
+        &self,
+        query: &str,
+        products: Vec<&str>,
+    ) -> ResponseFuture<SearchInstitutionsResponse>;
+    fn search_institutions_with_options(
+        &self,
+        query: &str,
+        products: Vec<&str>,
+        options: Option<SearchInstitutionsRequestOptions>,
+    ) -> ResponseFuture<SearchInstitutionsResponse>;
+    fn get_institution_by_id(&self, id: &str) -> ResponseFuture<GetInstitutionByIdResponse>;
+    fn get_institution_by_id_with_options(
+        &self,
+        id: &str,
+        options: Option<GetInstitutionByIdRequestOptions>,
+    ) -> ResponseFuture<GetInstitutionByIdResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncInstitutions for AsyncClient {
+    fn get_institutions(&self, count: u16, offset: u32) -> ResponseFuture<GetInstitutionsResponse> {
+        self.get_institutions_with_options(count, offset, None)
+    }
+
+    fn get_institutions_with_options(
+        &self,
+        count: u16,
+        offset: u32,
+        options: Option<GetInstitutionsRequestOptions>,
+    ) -> ResponseFuture<GetInstitutionsResponse> {
+        let mut result_count = count;
+        if result_count == 0 {
+            result_count = 50
+        }
+
+        let req = GetInstitutionsRequest {
+            count: result_count,
+            offset,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn search_institutions(
+        &self,
+        query: &str,
+        products: Vec<&str>,
+    ) -> ResponseFuture<SearchInstitutionsResponse> {
+        self.search_institutions_with_options(query, products, None)
+    }
+
+    fn search_institutions_with_options(
+        &self,
+        query: &str,
+        products: Vec<&str>,
+        options: Option<SearchInstitutionsRequestOptions>,
+    ) -> ResponseFuture<SearchInstitutionsResponse> {
+        if query.is_empty() {
+            return fail(Kind::EmptyQuery);
+        }
+
+        let req = SearchInstitutionsRequest {
+            query,
+            products,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn get_institution_by_id(&self, id: &str) -> ResponseFuture<GetInstitutionByIdResponse> {
+        self.get_institution_by_id_with_options(id, None)
+    }
+
+    fn get_institution_by_id_with_options(
+        &self,
+        id: &str,
+        options: Option<GetInstitutionByIdRequestOptions>,
+    ) -> ResponseFuture<GetInstitutionByIdResponse> {
+        if id.is_empty() {
+            return fail(Kind::EmptyId);
+        }
+
+        let req = GetInstitutionByIdRequest {
+            institution_id: id,
+            options,
+        };
+
+        self.send(&req)
+    }
+}
+
+/// InstitutionsApi groups the institutions endpoints, e.g.
+/// `client.institutions().get(..)`.
+pub struct InstitutionsApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl InstitutionsApi<'_, Client> {
+    pub fn get(&self, count: u16, offset: u32) -> Result<GetInstitutionsResponse, Error> {
+        self.client.get_institutions(count, offset)
+    }
+
+    pub fn get_with_options(
+        &self,
+        count: u16,
+        offset: u32,
+        options: Option<GetInstitutionsRequestOptions>,
+    ) -> Result<GetInstitutionsResponse, Error> {
+        self.client
+            .get_institutions_with_options(count, offset, options)
+    }
+
+    pub fn get_by_id(&self, id: &str) -> Result<GetInstitutionByIdResponse, Error> {
+        self.client.get_institution_by_id(id)
+    }
+
+    pub fn get_by_id_with_options(
+        &self,
+        id: &str,
+        options: Option<GetInstitutionByIdRequestOptions>,
+    ) -> Result<GetInstitutionByIdResponse, Error> {
+        self.client.get_institution_by_id_with_options(id, options)
+    }
+
+    pub fn search(
+        &self,
+        query: &str,
+        products: Vec<&str>,
+    ) -> Result<SearchInstitutionsResponse, Error> {
+        self.client.search_institutions(query, products)
+    }
+
+    pub fn search_with_options(
+        &self,
+        query: &str,
+        products: Vec<&str>,
+        options: Option<SearchInstitutionsRequestOptions>,
+    ) -> Result<SearchInstitutionsResponse, Error> {
+        self.client
+            .search_institutions_with_options(query, products, options)
+    }
+
+    /// Walks the whole institution catalogue, see `InstitutionsIter`.
+    pub fn iter<'b>(&self) -> InstitutionsIter<'b> {
+        InstitutionsIter::new(self.client)
+    }
+}
+
+#[cfg(feature = "async")]
+impl InstitutionsApi<'_, AsyncClient> {
+    pub fn get(&self, count: u16, offset: u32) -> ResponseFuture<GetInstitutionsResponse> {
+        self.client.get_institutions(count, offset)
+    }
+
+    pub fn get_with_options(
+        &self,
+        count: u16,
+        offset: u32,
+        options: Option<GetInstitutionsRequestOptions>,
+    ) -> ResponseFuture<GetInstitutionsResponse> {
+        self.client
+            .get_institutions_with_options(count, offset, options)
+    }
+
+    pub fn get_by_id(&self, id: &str) -> ResponseFuture<GetInstitutionByIdResponse> {
+        self.client.get_institution_by_id(id)
+    }
+
+    pub fn get_by_id_with_options(
+        &self,
+        id: &str,
+        options: Option<GetInstitutionByIdRequestOptions>,
+    ) -> ResponseFuture<GetInstitutionByIdResponse> {
+        self.client.get_institution_by_id_with_options(id, options)
+    }
+
+    pub fn search(
+        &self,
+        query: &str,
+        products: Vec<&str>,
+    ) -> ResponseFuture<SearchInstitutionsResponse> {
+        self.client.search_institutions(query, products)
+    }
+
+    pub fn search_with_options(
+        &self,
+        query: &str,
+        products: Vec<&str>,
+        options: Option<SearchInstitutionsRequestOptions>,
+    ) -> ResponseFuture<SearchInstitutionsResponse> {
+        self.client
+            .search_institutions_with_options(query, products, options)
+    }
+}
+
+/// InstitutionsIter walks the whole institution catalogue through
+/// `/institutions/get`, fetching pages of up to 500 institutions as it goes.
+/// Institutions that shift across a page boundary while the catalogue is
+/// walked are only returned once.
+///
+/// Pages rejected with `RATE_LIMIT_EXCEEDED` are fetched again after backing
+/// off, unless the client's retry policy already retries them, and
+/// `with_rate_limit` keeps the walk under a limit to begin with. Any other
+/// error ends the walk; `offset` then tells where to resume it.
+pub struct InstitutionsIter<'a> {
+    client: Client,
+    options: Option<GetInstitutionsRequestOptions<'a>>,
+    pager: Pager<Institution>,
+    rate_limiter: Option<RateLimiter>,
+    rate_limit_policy: RetryPolicy,
+}
+
+impl<'a> InstitutionsIter<'a> {
+    pub fn new(client: &Client) -> InstitutionsIter<'a> {
+        InstitutionsIter {
+            client: client.clone(),
+            options: None,
+            pager: Pager::new(),
+            rate_limiter: None,
+            rate_limit_policy: RetryPolicy::default()
+                .with_max_attempts(5)
+                .with_backoff(Duration::from_secs(1), Duration::from_secs(60)),
+        }
+    }
+
+    /// Only walks the institutions matching `options`.
+    pub fn with_options(
+        mut self,
+        options: GetInstitutionsRequestOptions<'a>,
+    ) -> InstitutionsIter<'a> {
+        self.options = Some(options);
+        self
+    }
+
+    /// Sets how many institutions are fetched per call, up to 500.
+    pub fn with_page_size(mut self, page_size: u16) -> InstitutionsIter<'a> {
+        self.pager.page_size = i64::from(page_size).clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Starts the walk at `offset`, e.g. to resume a walk that failed.
+    pub fn with_offset(mut self, offset: u32) -> InstitutionsIter<'a> {
+        self.pager.start_at(i64::from(offset));
+        self
+    }
+
+    /// Throttles the walk to `limit` calls to `/institutions/get`. The walk
+    /// has its own throttle, so the client's rate limiter, and any other
+    /// client sharing it, keeps its limits; calls still wait on both.
+    pub fn with_rate_limit(mut self, limit: RateLimit) -> InstitutionsIter<'a> {
+        self.rate_limiter =
+            Some(RateLimiter::new().with_endpoint_limit("/institutions/get", limit));
+        self
+    }
+
+    /// Sets how often and how long the walk backs off when a page is rejected
+    /// with `RATE_LIMIT_EXCEEDED`. By default a page is tried up to five
+    /// times, backing off from one second to a minute. The policy is not used
+    /// when the client's retry policy already retries rate limits.
+    pub fn with_rate_limit_policy(mut self, policy: RetryPolicy) -> InstitutionsIter<'a> {
+        self.rate_limit_policy = policy;
+        self
+    }
+
+    /// Returns the offset of the next institution the walk will return, which
+    /// a new walk can be started at with `with_offset`.
+    pub fn offset(&self) -> u32 {
+        self.pager.resume_offset() as u32
+    }
+
+    /// Returns the number of institutions in the catalogue, as of the last
+    /// page fetched.
+    pub fn total(&self) -> Option<u32> {
+        self.pager.total().map(|total| total as u32)
+    }
+
+    fn fetch(&self, offset: i64) -> Result<GetInstitutionsResponse, Error> {
+        let max_attempts = if self.client.retry_policy().retries_rate_limits() {
+            1
+        } else {
+            self.rate_limit_policy.max_attempts
+        };
+
+        let mut attempt = 1;
+        loop {
+            if let Some(ref rate_limiter) = self.rate_limiter {
+                thread::sleep(rate_limiter.acquire("/institutions/get", None));
+            }
+            let result = self.client.get_institutions_with_options(
+                self.pager.page_size as u16,
+                offset as u32,
+                self.options.clone(),
+            );
+            match result {
+                Err(ref err) if err.is_rate_limited() && attempt < max_attempts => {
+                    thread::sleep(self.rate_limit_policy.backoff(attempt));
+                    attempt += 1;
+                }
+                result => return result,
+            }
+        }
+    }
+}
+
+impl Iterator for InstitutionsIter<'_> {
+    type Item = Result<Institution, Error>;
+
+    fn next(&mut self) -> Option<Result<Institution, Error>> {
+        loop {
+            if let Some(institution) = self.pager.pop() {
+                return Some(Ok(institution));
+            }
+
+            let offset = self.pager.next_offset()?;
+            match self.fetch(offset) {
+                Ok(resp) => {
+                    self.pager
+                        .push_page(i64::from(resp.total), resp.institutions, |institution| {
+                            &institution.institution_id
+                        })
+                }
+                Err(err) => {
+                    self.pager.finish();
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::mock::MockPlaid;
+    use crate::transport::InMemoryTransport;
+
+    const RATE_LIMIT_EXCEEDED: &str = r#"{
+        "error_type": "RATE_LIMIT_EXCEEDED",
+        "error_code": "RATE_LIMIT",
+        "error_message": "rate limit exceeded",
+        "display_message": null,
+        "request_id": "HNTDNrA8F1shFEW"
+    }"#;
+
+    fn test_client() -> Client {
+        Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(MockPlaid::new())
+    }
+
+    #[test]
+    fn test_iter() {
+        let test_client = test_client();
+
+        let mut institutions = test_client.institutions().iter().with_page_size(4);
+        let ids: Vec<String> = institutions
+            .by_ref()
+            .map(|institution| institution.unwrap().institution_id)
+            .collect();
+
+        assert_eq!(6, ids.len());
+        assert_eq!(Some(6), institutions.total());
+        assert_eq!(6, institutions.offset());
+    }
+
+    #[test]
+    fn test_iter_resumes() {
+        let test_client = test_client();
+
+        let mut institutions = test_client.institutions().iter().with_page_size(4);
+        for _ in 0..3 {
+            institutions.next().unwrap().unwrap();
+        }
+        let offset = institutions.offset();
+        let rest = InstitutionsIter::new(&test_client)
+            .with_offset(offset)
+            .count();
+
+        assert_eq!(3, offset);
+        assert_eq!(3, rest);
+    }
+
+    #[test]
+    fn test_iter_waits_out_rate_limits() {
+        let transport = InMemoryTransport::new()
+            .with_status_response("/institutions/get", 429, RATE_LIMIT_EXCEEDED)
+            .with_response(
+                "/institutions/get",
+                include_str!("../fixtures/institutions/get.json"),
+            );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone());
+
+        let institutions: Vec<Institution> = test_client
+            .institutions()
+            .iter()
+            .with_rate_limit_policy(
+                RetryPolicy::default()
+                    .with_backoff(Duration::from_millis(0), Duration::from_millis(0)),
+            )
+            .collect::<Result<_, _>>()
+            .unwrap();
+
+        assert_eq!(6, institutions.len());
+        assert_eq!(2, transport.requests().len());
+    }
+
+    #[test]
+    fn test_iter_leaves_rate_limits_to_client_retries() {
+        let transport = InMemoryTransport::new().with_status_response(
+            "/institutions/get",
+            429,
+            RATE_LIMIT_EXCEEDED,
+        );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+            .with_retry_policy(
+                RetryPolicy::default()
+                    .with_max_attempts(2)
+                    .with_backoff(Duration::from_millis(0), Duration::from_millis(0)),
+            );
+
+        let err = test_client
+            .institutions()
+            .iter()
+            .next()
+            .unwrap()
+            .err()
+            .unwrap();
+
+        assert!(err.is_rate_limited());
+        assert_eq!(2, transport.requests().len());
+    }
+
+    #[test]
+    fn test_iter_rate_limit_keeps_shared_limiter() {
+        let limiter =
+            RateLimiter::new().with_endpoint_limit("/institutions/get", RateLimit::per_second(10));
+        let test_client = test_client().with_rate_limiter(limiter);
+        let other_client = test_client.clone();
+
+        let mut institutions = test_client
+            .institutions()
+            .iter()
+            .with_rate_limit(RateLimit::per_minute(1))
+            .with_page_size(500);
+        assert_eq!(6, institutions.by_ref().count());
+
+        let walk_limiter = institutions.rate_limiter.as_ref().unwrap();
+        assert!(walk_limiter.acquire("/institutions/get", None) > Duration::from_secs(50));
+        let shared = other_client.rate_limiter().unwrap();
+        assert!(shared.acquire("/institutions/get", None) < Duration::from_secs(1));
     }
 }
@@ -1,11 +1,17 @@
 use crate::accounts::Account;
+use crate::endpoint::Endpoint;
 use crate::errors::{Error, Kind};
 use crate::holdings::Security;
 use crate::items::Item;
+use crate::pagination::{Pager, MAX_PAGE_SIZE};
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
+#[cfg(feature = "async")]
+use futures::{Async, Future, Poll, Stream};
 use serde::*;
 
-trait InvestmentTransactions {
+pub trait InvestmentTransactions {
     fn get_investment_transactions(
         &self,
         access_token: &str,
@@ -26,7 +32,7 @@ pub struct InvestmentTransaction {
     pub investment_transaction_id: String,
     pub account_id: String,
     pub security_id: String,
-    pub cancel_transaction_id: String,
+    pub cancel_transaction_id: Option<String>,
     pub date: String,
     pub name: String,
     pub quantity: f64,
@@ -36,13 +42,11 @@ pub struct InvestmentTransaction {
     #[serde(rename = "type")]
     pub investment_transaction_type: String,
     pub iso_currency_code: String,
-    pub unofficial_currency_code: String,
+    pub unofficial_currency_code: Option<String>,
 }
 
 #[derive(Serialize)]
 struct GetInvestmentTransactionsRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     start_date: &'a str,
     end_date: &'a str,
@@ -50,11 +54,46 @@ struct GetInvestmentTransactionsRequest<'a> {
     options: Option<GetInvestmentTransactionsRequestOptions<'a>>,
 }
 
-#[derive(Serialize)]
-struct GetInvestmentTransactionsRequestOptions<'a> {
+impl Endpoint for GetInvestmentTransactionsRequest<'_> {
+    type Response = GetInvestmentTransactionsResponse;
+    const PATH: &'static str = "/investments/transactions/get";
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GetInvestmentTransactionsRequestOptions<'a> {
+    #[serde(skip_serializing_if = "Option::is_none")]
     account_ids: Option<Vec<&'a str>>,
+    #[serde(skip_serializing_if = "Option::is_none")]
     count: Option<i64>,
-    offsent: Option<i64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    offset: Option<i64>,
+}
+
+impl<'a> GetInvestmentTransactionsRequestOptions<'a> {
+    pub fn new() -> GetInvestmentTransactionsRequestOptions<'a> {
+        GetInvestmentTransactionsRequestOptions::default()
+    }
+
+    /// Only returns data for the accounts with these IDs.
+    pub fn with_account_ids(
+        mut self,
+        account_ids: Vec<&'a str>,
+    ) -> GetInvestmentTransactionsRequestOptions<'a> {
+        self.account_ids = Some(account_ids);
+        self
+    }
+
+    /// Caps how many transactions are returned, up to 500.
+    pub fn with_count(mut self, count: i64) -> GetInvestmentTransactionsRequestOptions<'a> {
+        self.count = Some(count);
+        self
+    }
+
+    /// Skips this many transactions, for paging through results.
+    pub fn with_offset(mut self, offset: i64) -> GetInvestmentTransactionsRequestOptions<'a> {
+        self.offset = Some(offset);
+        self
+    }
 }
 
 #[derive(Deserialize)]
@@ -67,7 +106,7 @@ pub struct GetInvestmentTransactionsResponse {
     pub total_investment_transactions: i64,
 }
 
-impl<'a> InvestmentTransactions for Client<'a> {
+impl InvestmentTransactions for Client {
     fn get_investment_transactions(
         &self,
         access_token: &str,
@@ -84,23 +123,439 @@ impl<'a> InvestmentTransactions for Client<'a> {
         end_date: &str,
         options: Option<GetInvestmentTransactionsRequestOptions>,
     ) -> Result<GetInvestmentTransactionsResponse, Error> {
-        if start_date == "" || end_date == "" {
+        if start_date.is_empty() || end_date.is_empty() {
             Err(Error::new(Kind::ValidationError(
                 "start date and end date must be specified",
             )))?
         }
 
         let req = GetInvestmentTransactionsRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             start_date,
             end_date,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/investments/transactions/get", &json_body))
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncInvestmentTransactions {
+    fn get_investment_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> ResponseFuture<GetInvestmentTransactionsResponse>;
+    fn get_investment_transactions_with_options(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        options: Option<GetInvestmentTransactionsRequestOptions>,
+    ) -> ResponseFuture<GetInvestmentTransactionsResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncInvestmentTransactions for AsyncClient {
+    fn get_investment_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> ResponseFuture<GetInvestmentTransactionsResponse> {
+        self.get_investment_transactions_with_options(access_token, start_date, end_date, None)
+    }
+
+    fn get_investment_transactions_with_options(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        options: Option<GetInvestmentTransactionsRequestOptions>,
+    ) -> ResponseFuture<GetInvestmentTransactionsResponse> {
+        if start_date.is_empty() || end_date.is_empty() {
+            return fail(Kind::ValidationError(
+                "start date and end date must be specified",
+            ));
+        }
+
+        let req = GetInvestmentTransactionsRequest {
+            access_token,
+            start_date,
+            end_date,
+            options,
+        };
+
+        self.send(&req)
+    }
+}
+
+/// InvestmentTransactionsApi groups the investment transactions endpoints, e.g.
+/// `client.investment_transactions().get(..)`.
+pub struct InvestmentTransactionsApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl InvestmentTransactionsApi<'_, Client> {
+    pub fn get(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<GetInvestmentTransactionsResponse, Error> {
+        self.client
+            .get_investment_transactions(access_token, start_date, end_date)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        options: Option<GetInvestmentTransactionsRequestOptions>,
+    ) -> Result<GetInvestmentTransactionsResponse, Error> {
+        self.client.get_investment_transactions_with_options(
+            access_token,
+            start_date,
+            end_date,
+            options,
+        )
+    }
+
+    /// Walks every investment transaction between `start_date` and
+    /// `end_date`, see `InvestmentTransactionsIter`.
+    pub fn iter(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> InvestmentTransactionsIter {
+        InvestmentTransactionsIter::new(self.client, access_token, start_date, end_date)
+    }
+}
+
+#[cfg(feature = "async")]
+impl InvestmentTransactionsApi<'_, AsyncClient> {
+    pub fn get(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> ResponseFuture<GetInvestmentTransactionsResponse> {
+        self.client
+            .get_investment_transactions(access_token, start_date, end_date)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        options: Option<GetInvestmentTransactionsRequestOptions>,
+    ) -> ResponseFuture<GetInvestmentTransactionsResponse> {
+        self.client.get_investment_transactions_with_options(
+            access_token,
+            start_date,
+            end_date,
+            options,
+        )
+    }
+
+    /// Streams every investment transaction between `start_date` and
+    /// `end_date`, see `InvestmentTransactionsStream`.
+    pub fn stream(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> InvestmentTransactionsStream {
+        InvestmentTransactionsStream::new(self.client, access_token, start_date, end_date)
+    }
+}
+
+// InvestmentTransactionPages is the state shared by
+// `InvestmentTransactionsIter` and `InvestmentTransactionsStream`.
+struct InvestmentTransactionPages {
+    access_token: String,
+    start_date: String,
+    end_date: String,
+    account_ids: Option<Vec<String>>,
+    pager: Pager<InvestmentTransaction>,
+    accounts: Vec<Account>,
+    item: Option<Item>,
+    securities: Vec<Security>,
+}
+
+impl InvestmentTransactionPages {
+    fn new(access_token: &str, start_date: &str, end_date: &str) -> InvestmentTransactionPages {
+        InvestmentTransactionPages {
+            access_token: access_token.to_string(),
+            start_date: start_date.to_string(),
+            end_date: end_date.to_string(),
+            account_ids: None,
+            pager: Pager::new(),
+            accounts: Vec::new(),
+            item: None,
+            securities: Vec::new(),
+        }
+    }
+
+    fn options(&self, offset: i64) -> GetInvestmentTransactionsRequestOptions<'_> {
+        let mut options = GetInvestmentTransactionsRequestOptions::new()
+            .with_count(self.pager.page_size)
+            .with_offset(offset);
+        if let Some(ref account_ids) = self.account_ids {
+            options = options.with_account_ids(account_ids.iter().map(String::as_str).collect());
+        }
+        options
+    }
+
+    fn receive(&mut self, resp: GetInvestmentTransactionsResponse) {
+        if self.pager.is_first_page() {
+            self.accounts = resp.accounts;
+            self.item = Some(resp.item);
+            self.securities = resp.securities;
+        }
+        self.pager.push_page(
+            resp.total_investment_transactions,
+            resp.investment_transactions,
+            |transaction| &transaction.investment_transaction_id,
+        );
+    }
+}
+
+/// InvestmentTransactionsIter walks every investment transaction in a date
+/// range, fetching pages of up to 500 transactions as it goes. Transactions
+/// that shift across a page boundary while the range is walked are only
+/// returned once.
+///
+/// The accounts, item and securities are taken from the first page, and can
+/// be read once the first transaction has been returned. The walk stops after
+/// the first error.
+pub struct InvestmentTransactionsIter {
+    client: Client,
+    pages: InvestmentTransactionPages,
+}
+
+impl InvestmentTransactionsIter {
+    pub fn new(
+        client: &Client,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> InvestmentTransactionsIter {
+        InvestmentTransactionsIter {
+            client: client.clone(),
+            pages: InvestmentTransactionPages::new(access_token, start_date, end_date),
+        }
+    }
+
+    /// Sets how many transactions are fetched per call, up to 500.
+    pub fn with_page_size(mut self, page_size: i64) -> InvestmentTransactionsIter {
+        self.pages.pager.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Only walks the investment transactions of the accounts with these IDs.
+    pub fn with_account_ids(mut self, account_ids: Vec<&str>) -> InvestmentTransactionsIter {
+        self.pages.account_ids = Some(account_ids.into_iter().map(str::to_string).collect());
+        self
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.pages.accounts
+    }
+
+    pub fn item(&self) -> Option<&Item> {
+        self.pages.item.as_ref()
+    }
+
+    pub fn securities(&self) -> &[Security] {
+        &self.pages.securities
+    }
+
+    /// Returns the number of investment transactions in the range, as of the
+    /// last page fetched.
+    pub fn total_investment_transactions(&self) -> Option<i64> {
+        self.pages.pager.total()
+    }
+}
+
+impl Iterator for InvestmentTransactionsIter {
+    type Item = Result<InvestmentTransaction, Error>;
+
+    fn next(&mut self) -> Option<Result<InvestmentTransaction, Error>> {
+        loop {
+            if let Some(transaction) = self.pages.pager.pop() {
+                return Some(Ok(transaction));
+            }
+
+            let offset = self.pages.pager.next_offset()?;
+            let result = self.client.get_investment_transactions_with_options(
+                &self.pages.access_token,
+                &self.pages.start_date,
+                &self.pages.end_date,
+                Some(self.pages.options(offset)),
+            );
+            match result {
+                Ok(resp) => self.pages.receive(resp),
+                Err(err) => {
+                    self.pages.pager.finish();
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// InvestmentTransactionsStream is the non-blocking counterpart of
+/// `InvestmentTransactionsIter`.
+#[cfg(feature = "async")]
+pub struct InvestmentTransactionsStream {
+    client: AsyncClient,
+    pages: InvestmentTransactionPages,
+    pending: Option<ResponseFuture<GetInvestmentTransactionsResponse>>,
+}
+
+#[cfg(feature = "async")]
+impl InvestmentTransactionsStream {
+    pub fn new(
+        client: &AsyncClient,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> InvestmentTransactionsStream {
+        InvestmentTransactionsStream {
+            client: client.clone(),
+            pages: InvestmentTransactionPages::new(access_token, start_date, end_date),
+            pending: None,
+        }
+    }
+
+    /// Sets how many transactions are fetched per call, up to 500.
+    pub fn with_page_size(mut self, page_size: i64) -> InvestmentTransactionsStream {
+        self.pages.pager.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Only streams the investment transactions of the accounts with these IDs.
+    pub fn with_account_ids(mut self, account_ids: Vec<&str>) -> InvestmentTransactionsStream {
+        self.pages.account_ids = Some(account_ids.into_iter().map(str::to_string).collect());
+        self
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.pages.accounts
+    }
+
+    pub fn item(&self) -> Option<&Item> {
+        self.pages.item.as_ref()
+    }
+
+    pub fn securities(&self) -> &[Security] {
+        &self.pages.securities
+    }
+
+    /// Returns the number of investment transactions in the range, as of the
+    /// last page fetched.
+    pub fn total_investment_transactions(&self) -> Option<i64> {
+        self.pages.pager.total()
+    }
+}
+
+#[cfg(feature = "async")]
+impl Stream for InvestmentTransactionsStream {
+    type Item = InvestmentTransaction;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<InvestmentTransaction>, Error> {
+        loop {
+            if let Some(transaction) = self.pages.pager.pop() {
+                return Ok(Async::Ready(Some(transaction)));
+            }
+
+            let polled = match self.pending {
+                Some(ref mut pending) => pending.poll(),
+                None => {
+                    let offset = match self.pages.pager.next_offset() {
+                        Some(offset) => offset,
+                        None => return Ok(Async::Ready(None)),
+                    };
+                    self.pending = Some(self.client.get_investment_transactions_with_options(
+                        &self.pages.access_token,
+                        &self.pages.start_date,
+                        &self.pages.end_date,
+                        Some(self.pages.options(offset)),
+                    ));
+                    continue;
+                }
+            };
+            match polled {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(resp)) => {
+                    self.pending = None;
+                    self.pages.receive(resp);
+                }
+                Err(err) => {
+                    self.pending = None;
+                    self.pages.pager.finish();
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::mock::MockPlaid;
+
+    const ACCESS_TOKEN: &str = "access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6";
+
+    #[test]
+    fn test_get_investment_transactions_with_options() {
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(MockPlaid::new());
+
+        let resp = test_client
+            .get_investment_transactions_with_options(
+                ACCESS_TOKEN,
+                "2019-08-01",
+                "2019-08-31",
+                Some(
+                    GetInvestmentTransactionsRequestOptions::new()
+                        .with_count(2)
+                        .with_offset(2),
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(4, resp.total_investment_transactions);
+        let dates: Vec<&str> = resp
+            .investment_transactions
+            .iter()
+            .map(|transaction| transaction.date.as_str())
+            .collect();
+        assert_eq!(vec!["2019-08-15", "2019-08-02"], dates);
+    }
+
+    #[test]
+    fn test_iter() {
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(MockPlaid::new());
+
+        let mut transactions = test_client
+            .investment_transactions()
+            .iter(ACCESS_TOKEN, "2019-08-01", "2019-08-31")
+            .with_page_size(3);
+        let count = transactions.by_ref().map(Result::unwrap).count();
+
+        assert_eq!(4, count);
+        assert!(!transactions.securities().is_empty());
+        assert!(!transactions.accounts().is_empty());
     }
 }
@@ -1,5 +1,9 @@
+use crate::endpoint::Endpoint;
 use crate::errors::{Error, Kind};
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
+use chrono::{DateTime, Utc};
 use serde::*;
 
 pub trait Items {
@@ -45,24 +49,47 @@ pub struct ItemError {
 
 #[derive(Serialize)]
 struct GetItemRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
 }
 
+impl Endpoint for GetItemRequest<'_> {
+    type Response = GetItemResponse;
+    const PATH: &'static str = "/item/get";
+}
+
+/// ItemStatus records when Plaid last updated the item's products.
+#[derive(Deserialize)]
+pub struct ItemStatus {
+    #[serde(default)]
+    pub investments: Option<ProductStatus>,
+    #[serde(default)]
+    pub transactions: Option<ProductStatus>,
+}
+
+#[derive(Deserialize)]
+pub struct ProductStatus {
+    pub last_successful_update: Option<DateTime<Utc>>,
+    pub last_failed_update: Option<DateTime<Utc>>,
+}
+
 #[derive(Deserialize)]
 pub struct GetItemResponse {
     pub request_id: String,
     pub item: Item,
+    #[serde(default)]
+    pub status: Option<ItemStatus>,
 }
 
 #[derive(Serialize)]
 struct RemoveItemRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
 }
 
+impl Endpoint for RemoveItemRequest<'_> {
+    type Response = RemoveItemResponse;
+    const PATH: &'static str = "/item/remove";
+}
+
 #[derive(Deserialize)]
 pub struct RemoveItemResponse {
     pub request_id: String,
@@ -71,12 +98,15 @@ pub struct RemoveItemResponse {
 
 #[derive(Serialize)]
 struct UpdateItemWebhookRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     webhook: &'a str,
 }
 
+impl Endpoint for UpdateItemWebhookRequest<'_> {
+    type Response = UpdateItemWebhookResponse;
+    const PATH: &'static str = "/item/webhook/update";
+}
+
 #[derive(Deserialize)]
 pub struct UpdateItemWebhookResponse {
     pub request_id: String,
@@ -85,11 +115,14 @@ pub struct UpdateItemWebhookResponse {
 
 #[derive(Serialize)]
 struct InvalidateAccessTokenRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
 }
 
+impl Endpoint for InvalidateAccessTokenRequest<'_> {
+    type Response = InvalidateAccessTokenResponse;
+    const PATH: &'static str = "/item/access_token/invalidate";
+}
+
 #[derive(Deserialize)]
 pub struct InvalidateAccessTokenResponse {
     pub request_id: String,
@@ -98,12 +131,15 @@ pub struct InvalidateAccessTokenResponse {
 
 #[derive(Serialize)]
 struct UpdateAccessTokenVersionRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     #[serde(rename = "access_token_v1")]
     access_token: &'a str,
 }
 
+impl Endpoint for UpdateAccessTokenVersionRequest<'_> {
+    type Response = UpdateAccessTokenVersionResponse;
+    const PATH: &'static str = "/item/access_token/update_version";
+}
+
 #[derive(Deserialize)]
 pub struct UpdateAccessTokenVersionResponse {
     pub request_id: String,
@@ -113,11 +149,14 @@ pub struct UpdateAccessTokenVersionResponse {
 
 #[derive(Serialize)]
 struct CreatePublicTokenRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
 }
 
+impl Endpoint for CreatePublicTokenRequest<'_> {
+    type Response = CreatePublicTokenResponse;
+    const PATH: &'static str = "/item/public_token/create";
+}
+
 #[derive(Deserialize)]
 pub struct CreatePublicTokenResponse {
     pub request_id: String,
@@ -126,11 +165,14 @@ pub struct CreatePublicTokenResponse {
 
 #[derive(Serialize)]
 struct ExchangePublicTokenRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     public_token: &'a str,
 }
 
+impl Endpoint for ExchangePublicTokenRequest<'_> {
+    type Response = ExchangePublicTokenResponse;
+    const PATH: &'static str = "/item/public_token/exchange";
+}
+
 #[derive(Deserialize)]
 pub struct ExchangePublicTokenResponse {
     pub request_id: String,
@@ -138,37 +180,25 @@ pub struct ExchangePublicTokenResponse {
     pub item_id: String,
 }
 
-impl<'a> Items for Client<'a> {
+impl Items for Client {
     fn get_item(&self, access_token: &str) -> Result<GetItemResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
-        let req = GetItemRequest {
-            client_id: self.client_id,
-            secret: self.secret,
-            access_token,
-        };
+        let req = GetItemRequest { access_token };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/item/get", &json_body))
+        self.send(&req)
     }
 
     fn remove_item(&self, access_token: &str) -> Result<RemoveItemResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
-        let req = RemoveItemRequest {
-            client_id: self.client_id,
-            secret: self.secret,
-            access_token,
-        };
+        let req = RemoveItemRequest { access_token };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/item/remove", &json_body))
+        self.send(&req)
     }
 
     fn update_item_webhook(
@@ -176,94 +206,280 @@ impl<'a> Items for Client<'a> {
         access_token: &str,
         webhook: &str,
     ) -> Result<UpdateItemWebhookResponse, Error> {
-        if access_token == "" || webhook == "" {
+        if access_token.is_empty() || webhook.is_empty() {
             return Err(Error::new(Kind::ValidationError(
                 "access token and webhook must be specified",
             )));
         }
 
         let req = UpdateItemWebhookRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             webhook,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/item/webhook/update", &json_body))
+        self.send(&req)
     }
 
     fn invalidate_access_token(
         &self,
         access_token: &str,
     ) -> Result<InvalidateAccessTokenResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
-        let req = InvalidateAccessTokenRequest {
-            client_id: self.client_id,
-            secret: self.secret,
-            access_token,
-        };
+        let req = InvalidateAccessTokenRequest { access_token };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/item/access_token/invalidate", &json_body))
+        self.send(&req)
     }
 
     fn update_access_token_version(
         &self,
         access_token: &str,
     ) -> Result<UpdateAccessTokenVersionResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
-        let req = UpdateAccessTokenVersionRequest {
-            client_id: self.client_id,
-            secret: self.secret,
-            access_token,
-        };
+        let req = UpdateAccessTokenVersionRequest { access_token };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/item/access_token/update_version", &json_body))
+        self.send(&req)
     }
 
     fn create_public_token(&self, access_token: &str) -> Result<CreatePublicTokenResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
-        let req = CreatePublicTokenRequest {
-            client_id: self.client_id,
-            secret: self.secret,
-            access_token,
-        };
+        let req = CreatePublicTokenRequest { access_token };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/item/public_token/create", &json_body))
+        self.send(&req)
     }
 
     fn exchange_public_token(
         &self,
         public_token: &str,
     ) -> Result<ExchangePublicTokenResponse, Error> {
-        if public_token == "" {
+        if public_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
-        let req = ExchangePublicTokenRequest {
-            client_id: self.client_id,
-            secret: self.secret,
-            public_token,
+        let req = ExchangePublicTokenRequest { public_token };
+
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncItems {
+    fn get_item(&self, access_token: &str) -> ResponseFuture<GetItemResponse>;
+    fn remove_item(&self, access_token: &str) -> ResponseFuture<RemoveItemResponse>;
+    fn update_item_webhook(
+        &self,
+        access_token: &str,
+        webhook: &str,
+    ) -> ResponseFuture<UpdateItemWebhookResponse>;
+    fn invalidate_access_token(
+        &self,
+        access_token: &str,
+    ) -> ResponseFuture<InvalidateAccessTokenResponse>;
+    fn update_access_token_version(
+        &self,
+        access_token: &str,
+    ) -> ResponseFuture<UpdateAccessTokenVersionResponse>;
+    fn create_public_token(&self, access_token: &str) -> ResponseFuture<CreatePublicTokenResponse>;
+    fn exchange_public_token(
+        &self,
+        public_token: &str,
+    ) -> ResponseFuture<ExchangePublicTokenResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncItems for AsyncClient {
+    fn get_item(&self, access_token: &str) -> ResponseFuture<GetItemResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetItemRequest { access_token };
+
+        self.send(&req)
+    }
+
+    fn remove_item(&self, access_token: &str) -> ResponseFuture<RemoveItemResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = RemoveItemRequest { access_token };
+
+        self.send(&req)
+    }
+
+    fn update_item_webhook(
+        &self,
+        access_token: &str,
+        webhook: &str,
+    ) -> ResponseFuture<UpdateItemWebhookResponse> {
+        if access_token.is_empty() || webhook.is_empty() {
+            return fail(Kind::ValidationError(
+                "access token and webhook must be specified",
+            ));
+        }
+
+        let req = UpdateItemWebhookRequest {
+            access_token,
+            webhook,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/item/public_token/exchange", &json_body))
+        self.send(&req)
+    }
+
+    fn invalidate_access_token(
+        &self,
+        access_token: &str,
+    ) -> ResponseFuture<InvalidateAccessTokenResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = InvalidateAccessTokenRequest { access_token };
+
+        self.send(&req)
+    }
+
+    fn update_access_token_version(
+        &self,
+        access_token: &str,
+    ) -> ResponseFuture<UpdateAccessTokenVersionResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = UpdateAccessTokenVersionRequest { access_token };
+
+        self.send(&req)
+    }
+
+    fn create_public_token(&self, access_token: &str) -> ResponseFuture<CreatePublicTokenResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = CreatePublicTokenRequest { access_token };
+
+        self.send(&req)
+    }
+
+    fn exchange_public_token(
+        &self,
+        public_token: &str,
+    ) -> ResponseFuture<ExchangePublicTokenResponse> {
+        if public_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = ExchangePublicTokenRequest { public_token };
+
+        self.send(&req)
+    }
+}
+
+/// ItemsApi groups the item endpoints, e.g.
+/// `client.items().get(..)`.
+pub struct ItemsApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl ItemsApi<'_, Client> {
+    pub fn get(&self, access_token: &str) -> Result<GetItemResponse, Error> {
+        self.client.get_item(access_token)
+    }
+
+    pub fn remove(&self, access_token: &str) -> Result<RemoveItemResponse, Error> {
+        self.client.remove_item(access_token)
+    }
+
+    pub fn update_webhook(
+        &self,
+        access_token: &str,
+        webhook: &str,
+    ) -> Result<UpdateItemWebhookResponse, Error> {
+        self.client.update_item_webhook(access_token, webhook)
+    }
+
+    pub fn invalidate_access_token(
+        &self,
+        access_token: &str,
+    ) -> Result<InvalidateAccessTokenResponse, Error> {
+        self.client.invalidate_access_token(access_token)
+    }
+
+    pub fn update_access_token_version(
+        &self,
+        access_token: &str,
+    ) -> Result<UpdateAccessTokenVersionResponse, Error> {
+        self.client.update_access_token_version(access_token)
+    }
+
+    pub fn create_public_token(
+        &self,
+        access_token: &str,
+    ) -> Result<CreatePublicTokenResponse, Error> {
+        self.client.create_public_token(access_token)
+    }
+
+    pub fn exchange_public_token(
+        &self,
+        public_token: &str,
+    ) -> Result<ExchangePublicTokenResponse, Error> {
+        self.client.exchange_public_token(public_token)
+    }
+}
+
+#[cfg(feature = "async")]
+impl ItemsApi<'_, AsyncClient> {
+    pub fn get(&self, access_token: &str) -> ResponseFuture<GetItemResponse> {
+        self.client.get_item(access_token)
+    }
+
+    pub fn remove(&self, access_token: &str) -> ResponseFuture<RemoveItemResponse> {
+        self.client.remove_item(access_token)
+    }
+
+    pub fn update_webhook(
+        &self,
+        access_token: &str,
+        webhook: &str,
+    ) -> ResponseFuture<UpdateItemWebhookResponse> {
+        self.client.update_item_webhook(access_token, webhook)
+    }
+
+    pub fn invalidate_access_token(
+        &self,
+        access_token: &str,
+    ) -> ResponseFuture<InvalidateAccessTokenResponse> {
+        self.client.invalidate_access_token(access_token)
+    }
+
+    pub fn update_access_token_version(
+        &self,
+        access_token: &str,
+    ) -> ResponseFuture<UpdateAccessTokenVersionResponse> {
+        self.client.update_access_token_version(access_token)
+    }
+
+    pub fn create_public_token(
+        &self,
+        access_token: &str,
+    ) -> ResponseFuture<CreatePublicTokenResponse> {
+        self.client.create_public_token(access_token)
+    }
+
+    pub fn exchange_public_token(
+        &self,
+        public_token: &str,
+    ) -> ResponseFuture<ExchangePublicTokenResponse> {
+        self.client.exchange_public_token(public_token)
     }
 }
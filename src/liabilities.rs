@@ -1,7 +1,10 @@
 use crate::accounts::Account;
+use crate::endpoint::Endpoint;
 use crate::errors::{Error, Kind};
 use crate::items::Item;
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
 use serde::*;
 
 pub trait Liabilities {
@@ -68,12 +71,24 @@ pub struct StudentLoanStatus {
     pub end_date: String,
 }
 
-#[derive(Serialize)]
+#[derive(Clone, Debug, Default, Serialize)]
 pub struct GetLiabilitiesRequestOptions {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub account_ids: Option<Vec<String>>,
 }
 
+impl GetLiabilitiesRequestOptions {
+    pub fn new() -> GetLiabilitiesRequestOptions {
+        GetLiabilitiesRequestOptions::default()
+    }
+
+    /// Only returns data for the accounts with these IDs.
+    pub fn with_account_ids(mut self, account_ids: Vec<&str>) -> GetLiabilitiesRequestOptions {
+        self.account_ids = Some(account_ids.into_iter().map(str::to_string).collect());
+        self
+    }
+}
+
 // StudentLoanRepaymentPlan contains details about the repayment plan of the
 // loan.
 #[derive(Deserialize)]
@@ -85,13 +100,16 @@ pub struct StudentLoanRepaymentPlan {
 
 #[derive(Serialize)]
 struct GetLiabilitiesRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     #[serde(skip_serializing_if = "Option::is_none")]
     options: Option<GetLiabilitiesRequestOptions>,
 }
 
+impl Endpoint for GetLiabilitiesRequest<'_> {
+    type Response = GetLiabilitiesResponse;
+    const PATH: &'static str = "/liabilities/get";
+}
+
 // GetLiabilitiesResponse is the response from /liabilities/get.
 #[derive(Deserialize)]
 pub struct GetLiabilitiesResponse {
@@ -106,7 +124,7 @@ pub struct LiabilityAccounts {
     pub student: Vec<StudentLoanLiability>,
 }
 
-impl<'a> Liabilities for Client<'a> {
+impl Liabilities for Client {
     fn get_liabilities(&self, access_token: &str) -> Result<GetLiabilitiesResponse, Error> {
         self.get_liabilities_with_options(access_token, None)
     }
@@ -116,19 +134,86 @@ impl<'a> Liabilities for Client<'a> {
         access_token: &str,
         options: Option<GetLiabilitiesRequestOptions>,
     ) -> Result<GetLiabilitiesResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
         let req = GetLiabilitiesRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/liabilities/get", &json_body))
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncLiabilities {
+    fn get_liabilities(&self, access_token: &str) -> ResponseFuture<GetLiabilitiesResponse>;
+    fn get_liabilities_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetLiabilitiesRequestOptions>,
+    ) -> ResponseFuture<GetLiabilitiesResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncLiabilities for AsyncClient {
+    fn get_liabilities(&self, access_token: &str) -> ResponseFuture<GetLiabilitiesResponse> {
+        self.get_liabilities_with_options(access_token, None)
+    }
+
+    fn get_liabilities_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetLiabilitiesRequestOptions>,
+    ) -> ResponseFuture<GetLiabilitiesResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = GetLiabilitiesRequest {
+            access_token,
+            options,
+        };
+
+        self.send(&req)
+    }
+}
+
+/// LiabilitiesApi groups the liabilities endpoints, e.g.
+/// `client.liabilities().get(..)`.
+pub struct LiabilitiesApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl LiabilitiesApi<'_, Client> {
+    pub fn get(&self, access_token: &str) -> Result<GetLiabilitiesResponse, Error> {
+        self.client.get_liabilities(access_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetLiabilitiesRequestOptions>,
+    ) -> Result<GetLiabilitiesResponse, Error> {
+        self.client
+            .get_liabilities_with_options(access_token, options)
+    }
+}
+
+#[cfg(feature = "async")]
+impl LiabilitiesApi<'_, AsyncClient> {
+    pub fn get(&self, access_token: &str) -> ResponseFuture<GetLiabilitiesResponse> {
+        self.client.get_liabilities(access_token)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        options: Option<GetLiabilitiesRequestOptions>,
+    ) -> ResponseFuture<GetLiabilitiesResponse> {
+        self.client
+            .get_liabilities_with_options(access_token, options)
     }
 }
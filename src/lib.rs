@@ -1,7 +1,13 @@
 pub mod accounts;
 pub mod assets;
 pub mod auth;
+pub mod bulk;
+#[cfg(feature = "async")]
+pub mod cancel;
+pub mod cassette;
 pub mod categories;
+pub mod cursors;
+pub mod endpoint;
 pub mod environments;
 pub mod errors;
 pub mod holdings;
@@ -11,7 +17,18 @@ pub mod institutions;
 pub mod investment_transactions;
 pub mod items;
 pub mod liabilities;
+pub mod logging;
+pub mod middleware;
+#[cfg(any(test, feature = "mock"))]
+pub mod mock;
+pub mod pagination;
 pub mod plaid;
+pub mod polling;
+pub mod prelude;
 pub mod processors;
+pub mod rate_limit;
+pub mod retry;
 pub mod sandbox;
+pub mod timeouts;
 pub mod transactions;
+pub mod transport;
@@ -0,0 +1,305 @@
+use crate::errors::Error;
+use crate::middleware::Middleware;
+use crate::transport::{HttpRequest, HttpResponse};
+use log::{debug, log_enabled, warn, Level};
+use serde_json::Value;
+use std::time::Duration;
+
+/// The log target every event of this crate is emitted under.
+pub const TARGET: &str = "plaid";
+
+const REDACTED: &str = "[REDACTED]";
+
+// Fields whose values are never written to the log: credentials and tokens,
+// account and routing numbers, and the identity data Plaid returns about
+// account holders.
+const SENSITIVE_FIELDS: &[&str] = &[
+    // credentials and tokens
+    "secret",
+    "public_key",
+    "access_token",
+    "access_token_v1",
+    "new_access_token",
+    "public_token",
+    "processor_token",
+    "stripe_bank_account_token",
+    "asset_report_token",
+    "audit_copy_token",
+    // account numbers
+    "account",
+    "account_number",
+    "routing",
+    "wire_routing",
+    "iban",
+    "bic",
+    "sort_code",
+    // identity
+    "names",
+    "emails",
+    "phone_numbers",
+    "addresses",
+    "first_name",
+    "middle_name",
+    "last_name",
+    "email",
+    "phone_number",
+    "ssn",
+    "date_of_birth",
+    "street",
+];
+
+// Fields that only hold account numbers within `numbers.eft`. Elsewhere, e.g.
+// in institution responses, the same names hold nothing sensitive.
+const EFT_FIELDS: &[&str] = &["branch", "institution"];
+
+/// Masks every sensitive field of a JSON body. Bodies that are not JSON are
+/// replaced entirely, since there is no telling what they contain.
+pub fn redact(body: &str) -> String {
+    match serde_json::from_str::<Value>(body) {
+        Ok(mut value) => {
+            redact_fields(&mut value, &|field: &mut Value| {
+                if !field.is_null() {
+                    *field = Value::String(REDACTED.to_string());
+                }
+            });
+            value.to_string()
+        }
+        Err(_) => format!("[{} bytes redacted]", body.len()),
+    }
+}
+
+// redact_fields hands the value of every sensitive field in `value` to
+// `mask`, which decides what to replace it with.
+pub(crate) fn redact_fields<F: Fn(&mut Value)>(value: &mut Value, mask: &F) {
+    redact_fields_under(value, None, mask)
+}
+
+// redact_fields_under walks `value`, which is held by the field `parent`.
+// Array items count as held by the array's field.
+fn redact_fields_under<F: Fn(&mut Value)>(value: &mut Value, parent: Option<&str>, mask: &F) {
+    match *value {
+        Value::Object(ref mut fields) => {
+            for (name, field) in fields.iter_mut() {
+                let name = name.as_str();
+                if SENSITIVE_FIELDS.contains(&name)
+                    || (parent == Some("eft") && EFT_FIELDS.contains(&name))
+                {
+                    mask(field);
+                } else {
+                    redact_fields_under(field, Some(name), mask);
+                }
+            }
+        }
+        Value::Array(ref mut items) => items
+            .iter_mut()
+            .for_each(|item| redact_fields_under(item, parent, mask)),
+        _ => {}
+    }
+}
+
+/// LoggingMiddleware logs every attempt at a call under the `plaid` target:
+/// endpoint, status, latency and Plaid request ID at debug level, or at warn
+/// level for failures. Clients run it ahead of any middleware they are given.
+///
+/// With bodies turned on, the request and response bodies are logged at debug
+/// level as well, after `redact` has masked them.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct LoggingMiddleware {
+    log_bodies: bool,
+}
+
+impl LoggingMiddleware {
+    pub fn new() -> LoggingMiddleware {
+        LoggingMiddleware::default()
+    }
+
+    pub fn with_bodies(mut self, log_bodies: bool) -> LoggingMiddleware {
+        self.log_bodies = log_bodies;
+        self
+    }
+}
+
+impl Middleware for LoggingMiddleware {
+    fn on_response(
+        &self,
+        request: &HttpRequest,
+        result: &mut Result<HttpResponse, Error>,
+        latency: Duration,
+    ) {
+        let latency_ms = latency.as_millis();
+
+        match *result {
+            Ok(ref response) => {
+                let level = if (200..300).contains(&response.status) {
+                    Level::Debug
+                } else {
+                    Level::Warn
+                };
+                if !log_enabled!(target: TARGET, level) {
+                    return;
+                }
+
+                let request_id = request_id_of(response).unwrap_or_default();
+                log::log!(
+                    target: TARGET,
+                    level,
+                    "endpoint={} status={} latency_ms={} request_id={}",
+                    request.endpoint,
+                    response.status,
+                    latency_ms,
+                    request_id
+                );
+
+                if self.log_bodies {
+                    debug!(
+                        target: TARGET,
+                        "endpoint={} request_body={}",
+                        request.endpoint,
+                        redact(&request.body)
+                    );
+                    debug!(
+                        target: TARGET,
+                        "endpoint={} response_body={}",
+                        request.endpoint,
+                        redact(&response.text())
+                    );
+                }
+            }
+            Err(ref err) => {
+                warn!(
+                    target: TARGET,
+                    "endpoint={} latency_ms={} {}",
+                    request.endpoint,
+                    latency_ms,
+                    describe(err)
+                );
+            }
+        }
+    }
+}
+
+// describe summarizes a failed attempt without the response body, which the
+// error's Display would otherwise include.
+fn describe(err: &Error) -> String {
+    match (err.status(), err.error_code()) {
+        (Some(status), Some(code)) => format!("status={} error_code={}", status, code.as_str()),
+        (Some(status), None) => format!("status={}", status),
+        (None, _) => format!("error={}", err),
+    }
+}
+
+fn request_id_of(response: &HttpResponse) -> Option<String> {
+    serde_json::from_slice::<Value>(&response.body)
+        .ok()
+        .and_then(|value| {
+            value
+                .get("request_id")
+                .and_then(|id| id.as_str())
+                .map(String::from)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::errors::Kind;
+
+    #[test]
+    fn test_redact() {
+        let body = r#"{
+            "client_id": "5d4ae5e1e28ac",
+            "secret": "4c5d1cd3a5ee",
+            "access_token": "access-sandbox-de3ce8ef",
+            "numbers": {
+                "ach": [{
+                    "account": "1111222233330000",
+                    "account_id": "BxBXxLj1m4HMXBm9WZZmCWVbPjX16EHwv99vp",
+                    "routing": "011401533",
+                    "wire_routing": "021000021"
+                }]
+            },
+            "user": {"ssn": "123-45-6789", "middle_name": null}
+        }"#;
+
+        let redacted: Value = serde_json::from_str(&redact(body)).unwrap();
+
+        assert_eq!("5d4ae5e1e28ac", redacted["client_id"]);
+        assert_eq!(REDACTED, redacted["secret"]);
+        assert_eq!(REDACTED, redacted["access_token"]);
+        assert_eq!(REDACTED, redacted["numbers"]["ach"][0]["account"]);
+        assert_eq!(REDACTED, redacted["numbers"]["ach"][0]["routing"]);
+        assert_eq!(REDACTED, redacted["numbers"]["ach"][0]["wire_routing"]);
+        assert_eq!(
+            "BxBXxLj1m4HMXBm9WZZmCWVbPjX16EHwv99vp",
+            redacted["numbers"]["ach"][0]["account_id"]
+        );
+        assert_eq!(REDACTED, redacted["user"]["ssn"]);
+        assert!(redacted["user"]["middle_name"].is_null());
+    }
+
+    #[test]
+    fn test_redact_eft_numbers() {
+        let body = r#"{
+            "numbers": {
+                "eft": [{
+                    "account": "111122223333",
+                    "account_id": "BxBXxLj1m4HMXBm9WZZmCWVbPjX16EHwv99vp",
+                    "institution": "021",
+                    "branch": "01140"
+                }]
+            }
+        }"#;
+
+        let redacted: Value = serde_json::from_str(&redact(body)).unwrap();
+
+        assert_eq!(REDACTED, redacted["numbers"]["eft"][0]["account"]);
+        assert_eq!(REDACTED, redacted["numbers"]["eft"][0]["institution"]);
+        assert_eq!(REDACTED, redacted["numbers"]["eft"][0]["branch"]);
+    }
+
+    #[test]
+    fn test_redact_leaves_institutions() {
+        let body = include_str!("../fixtures/institutions/get.json");
+        let institution = serde_json::json!({
+            "institution": serde_json::from_str::<Value>(body).unwrap()["institutions"][0],
+            "request_id": "m8MDnv9okwxFNBV"
+        });
+
+        let redacted: Value = serde_json::from_str(&redact(&institution.to_string())).unwrap();
+        assert_eq!(institution, redacted);
+
+        let redacted: Value = serde_json::from_str(&redact(body)).unwrap();
+        assert_eq!(serde_json::from_str::<Value>(body).unwrap(), redacted);
+    }
+
+    #[test]
+    fn test_redact_identity() {
+        let body = include_str!("../fixtures/identity/get.json");
+
+        let redacted = redact(body);
+
+        assert!(!redacted.contains("Alberta Bobbeth Charleson"));
+        assert!(!redacted.contains("accountholder0@example.com"));
+        assert!(!redacted.contains("2992 Cameron Road"));
+        assert!(!redacted.contains("1112223333"));
+    }
+
+    #[test]
+    fn test_describe_leaves_out_body() {
+        let err = Error::new(Kind::Status(
+            502,
+            r#"{"access_token": "access-sandbox-de3ce8ef"}"#.to_string(),
+        ));
+
+        assert_eq!("status=502", describe(&err));
+        assert_eq!(
+            "error=request timed out after 1s",
+            describe(&Error::new(Kind::Timeout(Duration::from_secs(1))))
+        );
+    }
+
+    #[test]
+    fn test_redact_non_json() {
+        assert_eq!("[11 bytes redacted]", redact("Bad Gateway"));
+    }
+}
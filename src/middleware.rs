@@ -0,0 +1,199 @@
+use crate::errors::Error;
+use crate::transport::{HttpRequest, HttpResponse};
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Middleware hooks into every attempt at a call, between the client and its
+/// transport. Layers run `on_request` in the order they were added, and
+/// `on_response` in reverse, so the first layer added wraps all the others.
+///
+/// Both hooks default to doing nothing, so a layer only implements what it
+/// needs.
+pub trait Middleware: Send + Sync {
+    /// Called with the prepared request before it is sent. The request can be
+    /// changed, e.g. to add headers. Returning a result short-circuits the
+    /// call: the transport and any later layers are skipped, and the result is
+    /// handed back through the layers already entered.
+    fn on_request(&self, _request: &mut HttpRequest) -> Option<Result<HttpResponse, Error>> {
+        None
+    }
+
+    /// Called with the raw response, or the error the transport failed with,
+    /// before it is decoded. `latency` is the time spent in the transport and
+    /// the layers after this one.
+    fn on_response(
+        &self,
+        _request: &HttpRequest,
+        _result: &mut Result<HttpResponse, Error>,
+        _latency: Duration,
+    ) {
+    }
+}
+
+impl<M: Middleware + ?Sized> Middleware for Arc<M> {
+    fn on_request(&self, request: &mut HttpRequest) -> Option<Result<HttpResponse, Error>> {
+        (**self).on_request(request)
+    }
+
+    fn on_response(
+        &self,
+        request: &HttpRequest,
+        result: &mut Result<HttpResponse, Error>,
+        latency: Duration,
+    ) {
+        (**self).on_response(request, result, latency)
+    }
+}
+
+// Chain is the ordered list of layers a client runs every attempt through.
+#[derive(Clone, Default)]
+pub(crate) struct Chain {
+    layers: Vec<Arc<dyn Middleware>>,
+}
+
+impl Chain {
+    pub(crate) fn push(&mut self, layer: Arc<dyn Middleware>) {
+        self.layers.push(layer);
+    }
+
+    // before runs `on_request` through the layers until one short-circuits.
+    // It returns how many layers were entered, which is how many `after` has
+    // to unwind.
+    pub(crate) fn before(
+        &self,
+        request: &mut HttpRequest,
+    ) -> (usize, Option<Result<HttpResponse, Error>>) {
+        for (i, layer) in self.layers.iter().enumerate() {
+            if let Some(result) = layer.on_request(request) {
+                return (i + 1, Some(result));
+            }
+        }
+        (self.layers.len(), None)
+    }
+
+    pub(crate) fn after(
+        &self,
+        entered: usize,
+        request: &HttpRequest,
+        result: &mut Result<HttpResponse, Error>,
+        latency: Duration,
+    ) {
+        for layer in self.layers[..entered].iter().rev() {
+            layer.on_response(request, result, latency);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::items::Items;
+    use crate::plaid::Client;
+    use crate::transport::InMemoryTransport;
+    use std::sync::Mutex;
+
+    const REMOVE_ITEM: &str = r#"{"request_id": "m8MDnv9okwxFNBV", "removed": true}"#;
+
+    struct Header(&'static str, &'static str);
+
+    impl Middleware for Header {
+        fn on_request(&self, request: &mut HttpRequest) -> Option<Result<HttpResponse, Error>> {
+            request
+                .headers
+                .push((self.0.to_string(), self.1.to_string()));
+            None
+        }
+    }
+
+    struct Canned(&'static str);
+
+    impl Middleware for Canned {
+        fn on_request(&self, _request: &mut HttpRequest) -> Option<Result<HttpResponse, Error>> {
+            Some(Ok(HttpResponse::json(200, self.0)))
+        }
+    }
+
+    #[derive(Default)]
+    struct Audit {
+        seen: Mutex<Vec<(String, u16)>>,
+    }
+
+    impl Middleware for Audit {
+        fn on_response(
+            &self,
+            request: &HttpRequest,
+            result: &mut Result<HttpResponse, Error>,
+            _latency: Duration,
+        ) {
+            if let Ok(ref response) = *result {
+                self.seen
+                    .lock()
+                    .unwrap()
+                    .push((request.endpoint.clone(), response.status));
+            }
+        }
+    }
+
+    #[test]
+    fn test_middleware_changes_request() {
+        let transport = InMemoryTransport::new().with_response("/item/remove", REMOVE_ITEM);
+        let audit = Arc::new(Audit::default());
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+            .with_middleware(Header("X-Trace-Id", "abc"))
+            .with_middleware(audit.clone());
+
+        test_client.remove_item("access-sandbox-token").unwrap();
+
+        let request = &transport.requests()[0];
+        assert!(request
+            .headers
+            .contains(&("X-Trace-Id".to_string(), "abc".to_string())));
+        assert_eq!(
+            vec![("/item/remove".to_string(), 200)],
+            *audit.seen.lock().unwrap()
+        );
+    }
+
+    #[test]
+    fn test_middleware_short_circuits() {
+        let transport = InMemoryTransport::new();
+        let audit = Arc::new(Audit::default());
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+            .with_middleware(audit.clone())
+            .with_middleware(Canned(REMOVE_ITEM))
+            .with_middleware(Header("X-Trace-Id", "abc"));
+
+        let resp = test_client.remove_item("access-sandbox-token").unwrap();
+
+        assert!(resp.removed);
+        assert!(transport.requests().is_empty());
+        assert_eq!(1, audit.seen.lock().unwrap().len());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_middleware_async() {
+        use crate::items::AsyncItems;
+        use crate::plaid::AsyncClient;
+
+        let transport = InMemoryTransport::new();
+        let audit = Arc::new(Audit::default());
+        let test_client =
+            AsyncClient::new("client_id", "secret", "public_key", Environment::SANDBOX)
+                .with_transport(transport.clone())
+                .with_middleware(audit.clone())
+                .with_middleware(Canned(REMOVE_ITEM));
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let resp = runtime
+            .block_on(test_client.remove_item("access-sandbox-token"))
+            .unwrap();
+
+        assert!(resp.removed);
+        assert!(transport.requests().is_empty());
+        assert_eq!(1, audit.seen.lock().unwrap().len());
+    }
+}
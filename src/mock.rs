@@ -0,0 +1,1097 @@
+use crate::errors::Error;
+use crate::transport::{normalize_endpoint, HttpRequest, HttpResponse, Transport};
+#[cfg(feature = "async")]
+use crate::transport::{AsyncTransport, TransportFuture};
+use chrono::{DateTime, Utc};
+#[cfg(feature = "async")]
+use futures::future;
+use rand::distributions::Alphanumeric;
+use rand::Rng;
+use serde_json::{json, Value};
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+
+const ACCOUNTS: &str = include_str!("../fixtures/accounts/get.json");
+const BALANCES: &str = include_str!("../fixtures/accounts/balance/get.json");
+const AUTH: &str = include_str!("../fixtures/auth/get.json");
+const CATEGORIES: &str = include_str!("../fixtures/categories/get.json");
+const EXCHANGE: &str = include_str!("../fixtures/item/public_token/exchange.json");
+const HOLDINGS: &str = include_str!("../fixtures/investments/holdings/get.json");
+const IDENTITY: &str = include_str!("../fixtures/identity/get.json");
+const INCOME: &str = include_str!("../fixtures/income/get.json");
+const INSTITUTIONS: &str = include_str!("../fixtures/institutions/get.json");
+const INVESTMENT_TRANSACTIONS: &str = include_str!("../fixtures/investments/transactions/get.json");
+const LIABILITIES: &str = include_str!("../fixtures/liabilities/get.json");
+const TRANSACTIONS: &str = include_str!("../fixtures/transactions/get.json");
+
+/// MockPlaid is a stateful, in-memory stand-in for the Plaid API. It serves the
+/// item, institution, product and processor endpoints this crate wraps, with
+/// data taken from the fixtures in this repository, and keeps track of items
+/// the way Plaid does: public tokens are created in the sandbox and exchanged
+/// once for an access token, access tokens can be rotated, items can be put
+/// into `ITEM_LOGIN_REQUIRED` and removed, and products move from available to
+/// billed the first time they are used.
+///
+/// The item from the fixtures is seeded, so its access token works from the
+/// start. An item put into `ITEM_LOGIN_REQUIRED` is repaired by exchanging a
+/// public token created for it with `/item/public_token/create`, which stands
+/// in for Link's update mode.
+///
+/// Asset reports are not modelled: calls to `/asset_report/*` fail with a 404
+/// `NOT_FOUND` error, like any other endpoint the mock does not serve.
+///
+/// It can be used in-process as the transport of a client, or served over
+/// HTTP by the `plaid-mock` binary. Both are only built with the `mock`
+/// feature. Clones share their state.
+#[derive(Clone)]
+pub struct MockPlaid {
+    inner: Arc<Mutex<MockState>>,
+}
+
+struct MockState {
+    credentials: Option<(String, String)>,
+    items: HashMap<String, MockItem>,
+    access_tokens: HashMap<String, String>,
+    public_tokens: HashMap<String, PendingItem>,
+    fixtures: Fixtures,
+}
+
+struct MockItem {
+    item_id: String,
+    access_token: String,
+    institution_id: String,
+    available_products: Vec<String>,
+    billed_products: Vec<String>,
+    webhook: String,
+    login_required: bool,
+    transactions_updated_at: Option<DateTime<Utc>>,
+}
+
+// PendingItem is what a public token will turn into when exchanged.
+enum PendingItem {
+    New {
+        institution_id: String,
+        products: Vec<String>,
+    },
+    Update {
+        item_id: String,
+    },
+}
+
+struct Fixtures {
+    accounts: Value,
+    balances: Value,
+    auth: Value,
+    categories: Value,
+    holdings: Value,
+    identity: Value,
+    income: Value,
+    institutions: Vec<Value>,
+    investment_transactions: Value,
+    liabilities: Value,
+    transactions: Value,
+}
+
+// MockError is a Plaid error response.
+struct MockError {
+    status: u16,
+    error_type: &'static str,
+    error_code: &'static str,
+    error_message: String,
+}
+
+impl MockError {
+    fn new(
+        status: u16,
+        error_type: &'static str,
+        error_code: &'static str,
+        error_message: &str,
+    ) -> MockError {
+        MockError {
+            status,
+            error_type,
+            error_code,
+            error_message: error_message.to_string(),
+        }
+    }
+
+    fn invalid_input(error_code: &'static str, error_message: &str) -> MockError {
+        MockError::new(400, "INVALID_INPUT", error_code, error_message)
+    }
+
+    fn invalid_request(error_code: &'static str, error_message: &str) -> MockError {
+        MockError::new(400, "INVALID_REQUEST", error_code, error_message)
+    }
+
+    fn missing(field: &str) -> MockError {
+        MockError::invalid_request(
+            "MISSING_FIELDS",
+            &format!("the following required fields are missing: {}", field),
+        )
+    }
+}
+
+type Reply = Result<Value, MockError>;
+
+type Handler = fn(&mut MockState, &Value) -> Reply;
+
+impl Default for MockPlaid {
+    fn default() -> MockPlaid {
+        MockPlaid::new()
+    }
+}
+
+impl MockPlaid {
+    /// Creates a mock seeded with the item from the fixtures. Any non-empty
+    /// client ID and secret are accepted.
+    pub fn new() -> MockPlaid {
+        let fixtures = Fixtures::load();
+        let exchange = parse(EXCHANGE);
+        let seeded = &fixtures.accounts["item"];
+
+        let item = MockItem {
+            item_id: str_of(&exchange, "item_id").to_string(),
+            access_token: str_of(&exchange, "access_token").to_string(),
+            institution_id: str_of(seeded, "institution_id").to_string(),
+            available_products: strings_of(&seeded["available_products"]),
+            billed_products: strings_of(&seeded["billed_products"]),
+            webhook: str_of(seeded, "webhook").to_string(),
+            login_required: false,
+            transactions_updated_at: None,
+        };
+
+        let mut state = MockState {
+            credentials: None,
+            items: HashMap::new(),
+            access_tokens: HashMap::new(),
+            public_tokens: HashMap::new(),
+            fixtures,
+        };
+        state
+            .access_tokens
+            .insert(item.access_token.clone(), item.item_id.clone());
+        state.items.insert(item.item_id.clone(), item);
+
+        MockPlaid {
+            inner: Arc::new(Mutex::new(state)),
+        }
+    }
+
+    /// Only accepts calls made with this client ID and secret.
+    pub fn with_credentials(self, client_id: &str, secret: &str) -> MockPlaid {
+        self.inner.lock().unwrap().credentials = Some((client_id.to_string(), secret.to_string()));
+        self
+    }
+
+    /// Answers a call to `endpoint` with a JSON `body`.
+    pub fn handle(&self, endpoint: &str, body: &str) -> HttpResponse {
+        let reply = match serde_json::from_str::<Value>(body) {
+            Ok(ref body) => self.inner.lock().unwrap().route(endpoint, body),
+            _ => Err(MockError::invalid_request(
+                "INVALID_BODY",
+                "body could not be parsed as JSON",
+            )),
+        };
+
+        match reply {
+            Ok(mut value) => {
+                value["request_id"] = Value::String(request_id());
+                HttpResponse::json(200, &value.to_string())
+            }
+            Err(err) => {
+                let value = json!({
+                    "display_message": null,
+                    "error_code": err.error_code,
+                    "error_message": err.error_message,
+                    "error_type": err.error_type,
+                    "request_id": request_id(),
+                });
+                HttpResponse::json(err.status, &value.to_string())
+            }
+        }
+    }
+}
+
+impl Transport for MockPlaid {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        Ok(self.handle(&request.endpoint, &request.body))
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for MockPlaid {
+    fn send(&self, request: HttpRequest) -> TransportFuture {
+        Box::new(future::ok(self.handle(&request.endpoint, &request.body)))
+    }
+}
+
+impl MockState {
+    fn route(&mut self, endpoint: &str, body: &Value) -> Reply {
+        let endpoint = normalize_endpoint(endpoint);
+        let (authenticated, handler): (bool, Handler) = match endpoint.as_str() {
+            "/sandbox/public_token/create" => (false, MockState::create_sandbox_public_token),
+            "/sandbox/item/reset_login" => (true, MockState::reset_login),
+            "/item/public_token/exchange" => (true, MockState::exchange_public_token),
+            "/item/public_token/create" => (true, MockState::create_public_token),
+            "/item/get" => (true, MockState::get_item),
+            "/item/remove" => (true, MockState::remove_item),
+            "/item/webhook/update" => (true, MockState::update_webhook),
+            "/item/access_token/invalidate" => (true, MockState::invalidate_access_token),
+            "/item/access_token/update_version" => (true, MockState::update_access_token_version),
+            "/categories/get" => (false, MockState::get_categories),
+            "/institutions/get" => (true, MockState::get_institutions),
+            "/institutions/get_by_id" => (false, MockState::get_institution_by_id),
+            "/institutions/search" => (false, MockState::search_institutions),
+            "/accounts/get" => (true, MockState::get_accounts),
+            "/accounts/balance/get" => (true, MockState::get_balances),
+            "/auth/get" => (true, MockState::get_auth),
+            "/identity/get" => (true, MockState::get_identity),
+            "/income/get" => (true, MockState::get_income),
+            "/investments/holdings/get" => (true, MockState::get_holdings),
+            "/investments/transactions/get" => (true, MockState::get_investment_transactions),
+            "/liabilities/get" => (true, MockState::get_liabilities),
+            "/transactions/get" => (true, MockState::get_transactions),
+            "/transactions/refresh" => (true, MockState::refresh_transactions),
+            "/transactions/sync" => (true, MockState::sync_transactions),
+            "/processor/apex/processor_token/create"
+            | "/processor/dwolla/processor_token/create" => {
+                (true, MockState::create_processor_token)
+            }
+            "/processor/stripe/bank_account_token/create" => (true, MockState::create_stripe_token),
+            _ => {
+                return Err(MockError::new(
+                    404,
+                    "INVALID_REQUEST",
+                    "NOT_FOUND",
+                    &format!("{} is not served by the mock", endpoint),
+                ))
+            }
+        };
+
+        if authenticated {
+            self.check_credentials(body)?;
+        }
+        handler(self, body)
+    }
+
+    fn get_categories(&mut self, _body: &Value) -> Reply {
+        Ok(self.fixtures.categories.clone())
+    }
+
+    fn get_accounts(&mut self, body: &Value) -> Reply {
+        let accounts = self.fixtures.accounts.clone();
+        self.product_response(body, None, accounts)
+    }
+
+    fn get_balances(&mut self, body: &Value) -> Reply {
+        let balances = self.fixtures.balances.clone();
+        self.product_response(body, Some("balance"), balances)
+    }
+
+    fn get_auth(&mut self, body: &Value) -> Reply {
+        let mut auth = self.fixtures.auth.clone();
+        if let Some(account_ids) = account_ids_of(body) {
+            let numbers = auth["numbers"].as_object_mut().into_iter().flatten();
+            for (_, numbers) in numbers {
+                retain_accounts(numbers, &account_ids);
+            }
+        }
+        self.product_response(body, Some("auth"), auth)
+    }
+
+    fn get_identity(&mut self, body: &Value) -> Reply {
+        let identity = self.fixtures.identity.clone();
+        self.product_response(body, Some("identity"), identity)
+    }
+
+    fn get_income(&mut self, body: &Value) -> Reply {
+        self.product_item(body, Some("income"))?;
+        Ok(self.fixtures.income.clone())
+    }
+
+    fn get_holdings(&mut self, body: &Value) -> Reply {
+        let mut holdings = self.fixtures.holdings.clone();
+        if let Some(account_ids) = account_ids_of(body) {
+            retain_accounts(&mut holdings["holdings"], &account_ids);
+        }
+        self.product_response(body, Some("investments"), holdings)
+    }
+
+    fn get_investment_transactions(&mut self, body: &Value) -> Reply {
+        let transactions = self.fixtures.investment_transactions.clone();
+        self.transactions_response(
+            body,
+            "investments",
+            transactions,
+            "investment_transactions",
+            "total_investment_transactions",
+        )
+    }
+
+    fn get_liabilities(&mut self, body: &Value) -> Reply {
+        let mut liabilities = self.fixtures.liabilities.clone();
+        if let Some(account_ids) = account_ids_of(body) {
+            retain_accounts(&mut liabilities["liabilities"]["student"], &account_ids);
+        }
+        self.product_response(body, Some("liabilities"), liabilities)
+    }
+
+    fn get_transactions(&mut self, body: &Value) -> Reply {
+        let transactions = self.fixtures.transactions.clone();
+        self.transactions_response(
+            body,
+            "transactions",
+            transactions,
+            "transactions",
+            "total_transactions",
+        )
+    }
+
+    // refresh_transactions completes the update right away, so it is visible
+    // in the item's status as soon as the call returns.
+    fn refresh_transactions(&mut self, body: &Value) -> Reply {
+        let item_id = self
+            .product_item(body, Some("transactions"))?
+            .item_id
+            .clone();
+        self.items
+            .get_mut(&item_id)
+            .unwrap()
+            .transactions_updated_at = Some(Utc::now());
+        Ok(json!({}))
+    }
+
+    // sync_transactions serves the fixture transactions as added, oldest
+    // first. Cursors record how many transactions have been served so far.
+    fn sync_transactions(&mut self, body: &Value) -> Reply {
+        let count = body["count"].as_u64().unwrap_or(100);
+        if !(1..=500).contains(&count) {
+            return Err(MockError::invalid_request(
+                "INVALID_FIELD",
+                "count must be at least 1 and at most 500",
+            ));
+        }
+        let offset = match body["cursor"].as_str() {
+            None | Some("") => 0,
+            Some(cursor) => cursor
+                .strip_prefix("cursor-")
+                .and_then(|offset| offset.parse::<usize>().ok())
+                .ok_or_else(|| MockError::invalid_request("INVALID_FIELD", "cursor is invalid"))?,
+        };
+        self.product_item(body, Some("transactions"))?;
+
+        let mut transactions = self.fixtures.transactions["transactions"]
+            .as_array()
+            .cloned()
+            .unwrap_or_default();
+        transactions.sort_by(|a, b| str_of(a, "date").cmp(str_of(b, "date")));
+        let total = transactions.len();
+        let added: Vec<Value> = transactions
+            .into_iter()
+            .skip(offset)
+            .take(count as usize)
+            .collect();
+        let next = (offset + added.len()).min(total);
+
+        Ok(json!({
+            "added": added,
+            "modified": [],
+            "removed": [],
+            "next_cursor": format!("cursor-{}", next),
+            "has_more": next < total,
+        }))
+    }
+
+    fn create_processor_token(&mut self, body: &Value) -> Reply {
+        self.processor_account(body)?;
+        Ok(json!({ "processor_token": format!("processor-sandbox-{}", uuid()) }))
+    }
+
+    fn create_stripe_token(&mut self, body: &Value) -> Reply {
+        self.processor_account(body)?;
+        Ok(json!({ "stripe_bank_account_token": format!("btok_{}", random_string(24)) }))
+    }
+
+    fn check_credentials(&self, body: &Value) -> Result<(), MockError> {
+        let client_id = required_str(body, "client_id")?;
+        let secret = required_str(body, "secret")?;
+        match self.credentials {
+            Some((ref expected_id, ref expected_secret))
+                if expected_id != client_id || expected_secret != secret =>
+            {
+                Err(MockError::invalid_input(
+                    "INVALID_API_KEYS",
+                    "invalid client_id or secret provided",
+                ))
+            }
+            _ => Ok(()),
+        }
+    }
+
+    fn institution(&self, institution_id: &str) -> Result<&Value, MockError> {
+        self.fixtures
+            .institutions
+            .iter()
+            .find(|institution| str_of(institution, "institution_id") == institution_id)
+            .ok_or_else(|| {
+                MockError::invalid_input("INVALID_INSTITUTION", "invalid institution_id provided")
+            })
+    }
+
+    fn item_id_for(&self, body: &Value) -> Result<String, MockError> {
+        let access_token = required_str(body, "access_token")?;
+        self.access_tokens
+            .get(access_token)
+            .cloned()
+            .ok_or_else(|| {
+                MockError::invalid_input(
+                    "INVALID_ACCESS_TOKEN",
+                    "provided access token is in an invalid format or no longer exists",
+                )
+            })
+    }
+
+    // product_item looks up the item a data call is made for, fails if it
+    // needs to log in again, and bills `product` on first use.
+    fn product_item(
+        &mut self,
+        body: &Value,
+        product: Option<&str>,
+    ) -> Result<&MockItem, MockError> {
+        let item_id = self.item_id_for(body)?;
+        let item = self.items.get_mut(&item_id).unwrap();
+
+        if item.login_required {
+            return Err(item_login_required());
+        }
+        if let Some(product) = product {
+            if !item.billed_products.iter().any(|billed| billed == product) {
+                let available = item
+                    .available_products
+                    .iter()
+                    .position(|available| available == product)
+                    .ok_or_else(|| {
+                        MockError::new(
+                            400,
+                            "ITEM_ERROR",
+                            "PRODUCTS_NOT_SUPPORTED",
+                            &format!("{} is not supported by this item", product),
+                        )
+                    })?;
+                item.available_products.remove(available);
+                item.billed_products.push(product.to_string());
+                item.billed_products.sort();
+            }
+        }
+        Ok(item)
+    }
+
+    // product_response fills a fixture in with the item it was requested for
+    // and narrows its accounts to `options.account_ids`.
+    fn product_response(
+        &mut self,
+        body: &Value,
+        product: Option<&str>,
+        mut response: Value,
+    ) -> Reply {
+        let item = self.product_item(body, product)?.to_json();
+        response["item"] = item;
+
+        if let Some(account_ids) = account_ids_of(body) {
+            for account_id in &account_ids {
+                let known = has_account(&response["accounts"], account_id);
+                if !known {
+                    return Err(MockError::invalid_input(
+                        "INVALID_ACCOUNT_ID",
+                        "one or more of the account IDs is invalid",
+                    ));
+                }
+            }
+            retain_accounts(&mut response["accounts"], &account_ids);
+        }
+        Ok(response)
+    }
+
+    // transactions_response serves a page of transactions between
+    // `start_date` and `end_date`, newest first.
+    fn transactions_response(
+        &mut self,
+        body: &Value,
+        product: &str,
+        response: Value,
+        list: &str,
+        total: &str,
+    ) -> Reply {
+        let start_date = required_str(body, "start_date")?.to_string();
+        let end_date = required_str(body, "end_date")?.to_string();
+        if start_date > end_date {
+            return Err(MockError::invalid_request(
+                "INVALID_FIELD",
+                "start_date must be before end_date",
+            ));
+        }
+
+        let count = body["options"]["count"].as_u64().unwrap_or(100);
+        if !(1..=500).contains(&count) {
+            return Err(MockError::invalid_request(
+                "INVALID_FIELD",
+                "count must be at least 1 and at most 500",
+            ));
+        }
+        let offset = body["options"]["offset"].as_u64().unwrap_or(0) as usize;
+
+        let mut response = self.product_response(body, Some(product), response)?;
+        let account_ids = account_ids_of(body);
+        let mut matching: Vec<Value> = response[list]
+            .as_array()
+            .cloned()
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|transaction| {
+                let date = str_of(transaction, "date");
+                date >= start_date.as_str() && date <= end_date.as_str()
+            })
+            .filter(|transaction| match account_ids {
+                Some(ref account_ids) => account_ids
+                    .iter()
+                    .any(|account_id| account_id == str_of(transaction, "account_id")),
+                None => true,
+            })
+            .collect();
+        matching.sort_by(|a, b| str_of(b, "date").cmp(str_of(a, "date")));
+
+        response[total] = json!(matching.len());
+        response[list] = Value::Array(
+            matching
+                .into_iter()
+                .skip(offset)
+                .take(count as usize)
+                .collect(),
+        );
+        Ok(response)
+    }
+
+    fn processor_account(&mut self, body: &Value) -> Result<(), MockError> {
+        let account_id = required_str(body, "account_id")?.to_string();
+        self.product_item(body, Some("auth"))?;
+
+        let known = has_account(&self.fixtures.accounts["accounts"], &account_id);
+        if known {
+            Ok(())
+        } else {
+            Err(MockError::invalid_input(
+                "INVALID_ACCOUNT_ID",
+                "the account_id is not valid for this item",
+            ))
+        }
+    }
+
+    fn create_sandbox_public_token(&mut self, body: &Value) -> Reply {
+        required_str(body, "public_key")?;
+        let institution_id = required_str(body, "institution_id")?.to_string();
+        let products = strings_of(&body["initial_products"]);
+        if products.is_empty() {
+            return Err(MockError::missing("initial_products"));
+        }
+
+        let supported = strings_of(&self.institution(&institution_id)?["products"]);
+        if let Some(product) = products.iter().find(|product| !supported.contains(product)) {
+            return Err(MockError::invalid_input(
+                "INVALID_PRODUCT",
+                &format!("{} is not supported by {}", product, institution_id),
+            ));
+        }
+
+        let public_token = format!("public-sandbox-{}", uuid());
+        self.public_tokens.insert(
+            public_token.clone(),
+            PendingItem::New {
+                institution_id,
+                products,
+            },
+        );
+        Ok(json!({ "public_token": public_token }))
+    }
+
+    fn reset_login(&mut self, body: &Value) -> Reply {
+        let item_id = self.item_id_for(body)?;
+        self.items.get_mut(&item_id).unwrap().login_required = true;
+        Ok(json!({ "reset_login": true }))
+    }
+
+    fn exchange_public_token(&mut self, body: &Value) -> Reply {
+        let public_token = required_str(body, "public_token")?;
+        let pending = self.public_tokens.remove(public_token).ok_or_else(|| {
+            MockError::invalid_input(
+                "INVALID_PUBLIC_TOKEN",
+                "provided public token is expired or has already been exchanged",
+            )
+        })?;
+
+        let item = match pending {
+            PendingItem::New {
+                institution_id,
+                products,
+            } => self.add_item(institution_id, products),
+            PendingItem::Update { item_id } => match self.items.get_mut(&item_id) {
+                Some(item) => {
+                    item.login_required = false;
+                    item
+                }
+                None => {
+                    return Err(MockError::invalid_input(
+                        "INVALID_PUBLIC_TOKEN",
+                        "the item of this public token has been removed",
+                    ))
+                }
+            },
+        };
+        Ok(json!({ "access_token": item.access_token, "item_id": item.item_id }))
+    }
+
+    fn add_item(&mut self, institution_id: String, billed_products: Vec<String>) -> &mut MockItem {
+        let available_products = self
+            .institution(&institution_id)
+            .map(|institution| strings_of(&institution["products"]))
+            .unwrap_or_default()
+            .into_iter()
+            .filter(|product| !billed_products.contains(product))
+            .collect();
+
+        let item = MockItem {
+            item_id: random_string(37),
+            access_token: format!("access-sandbox-{}", uuid()),
+            institution_id,
+            available_products,
+            billed_products,
+            webhook: String::new(),
+            login_required: false,
+            transactions_updated_at: None,
+        };
+        let item_id = item.item_id.clone();
+        self.access_tokens
+            .insert(item.access_token.clone(), item_id.clone());
+        self.items.entry(item_id).or_insert(item)
+    }
+
+    fn create_public_token(&mut self, body: &Value) -> Reply {
+        let item_id = self.item_id_for(body)?;
+        let public_token = format!("public-sandbox-{}", uuid());
+        self.public_tokens
+            .insert(public_token.clone(), PendingItem::Update { item_id });
+        Ok(json!({ "public_token": public_token }))
+    }
+
+    fn get_item(&mut self, body: &Value) -> Reply {
+        let item_id = self.item_id_for(body)?;
+        let item = &self.items[&item_id];
+        Ok(json!({ "item": item.to_json(), "status": item.status_json() }))
+    }
+
+    fn remove_item(&mut self, body: &Value) -> Reply {
+        let item_id = self.item_id_for(body)?;
+        if let Some(item) = self.items.remove(&item_id) {
+            self.access_tokens.remove(&item.access_token);
+        }
+        Ok(json!({ "removed": true }))
+    }
+
+    fn update_webhook(&mut self, body: &Value) -> Reply {
+        let webhook = required_str(body, "webhook")?.to_string();
+        let item_id = self.item_id_for(body)?;
+        let item = self.items.get_mut(&item_id).unwrap();
+        item.webhook = webhook;
+        Ok(json!({ "item": item.to_json() }))
+    }
+
+    fn invalidate_access_token(&mut self, body: &Value) -> Reply {
+        let item_id = self.item_id_for(body)?;
+        let item = self.items.get_mut(&item_id).unwrap();
+        let new_access_token = format!("access-sandbox-{}", uuid());
+
+        self.access_tokens.remove(&item.access_token);
+        self.access_tokens
+            .insert(new_access_token.clone(), item_id.clone());
+        item.access_token = new_access_token.clone();
+        Ok(json!({ "new_access_token": new_access_token }))
+    }
+
+    // update_access_token_version treats every legacy token as belonging to
+    // an item at the seeded institution that has not been migrated yet.
+    fn update_access_token_version(&mut self, body: &Value) -> Reply {
+        required_str(body, "access_token_v1")?;
+        let seeded = &self.fixtures.accounts["item"];
+        let institution_id = str_of(seeded, "institution_id").to_string();
+        let products = strings_of(&seeded["billed_products"]);
+
+        let item = self.add_item(institution_id, products);
+        Ok(json!({ "access_token": item.access_token, "item_id": item.item_id }))
+    }
+
+    fn get_institutions(&mut self, body: &Value) -> Reply {
+        let count = body["count"].as_u64().unwrap_or(0);
+        if !(1..=500).contains(&count) {
+            return Err(MockError::invalid_request(
+                "INVALID_FIELD",
+                "count must be at least 1 and at most 500",
+            ));
+        }
+        let offset = body["offset"].as_u64().unwrap_or(0) as usize;
+
+        let matching = self.matching_institutions(&body["options"], |_| true);
+        Ok(json!({
+            "total": matching.len(),
+            "institutions": matching
+                .into_iter()
+                .skip(offset)
+                .take(count as usize)
+                .collect::<Vec<_>>(),
+        }))
+    }
+
+    fn get_institution_by_id(&mut self, body: &Value) -> Reply {
+        required_str(body, "public_key")?;
+        let institution_id = required_str(body, "institution_id")?;
+        Ok(json!({ "institution": self.institution(institution_id)?.clone() }))
+    }
+
+    fn search_institutions(&mut self, body: &Value) -> Reply {
+        required_str(body, "public_key")?;
+        let query = required_str(body, "query")?.to_lowercase();
+
+        let mut options = body["options"].clone();
+        if options.is_null() {
+            options = json!({});
+        }
+        options["products"] = body["products"].clone();
+
+        let matching = self.matching_institutions(&options, |institution| {
+            str_of(institution, "name").to_lowercase().contains(&query)
+        });
+        Ok(json!({ "institutions": matching }))
+    }
+
+    fn matching_institutions<F>(&self, options: &Value, matches: F) -> Vec<Value>
+    where
+        F: Fn(&Value) -> bool,
+    {
+        let products = strings_of(&options["products"]);
+        let country_codes = strings_of(&options["country_codes"]);
+
+        self.fixtures
+            .institutions
+            .iter()
+            .filter(|institution| {
+                let supported = strings_of(&institution["products"]);
+                products.iter().all(|product| supported.contains(product))
+            })
+            .filter(|institution| {
+                let countries = strings_of(&institution["country_codes"]);
+                country_codes.is_empty()
+                    || country_codes.iter().any(|code| countries.contains(code))
+            })
+            .filter(|institution| matches(institution))
+            .cloned()
+            .collect()
+    }
+}
+
+impl MockItem {
+    fn status_json(&self) -> Value {
+        json!({
+            "transactions": {
+                "last_successful_update": self.transactions_updated_at,
+                "last_failed_update": null,
+            },
+        })
+    }
+
+    fn to_json(&self) -> Value {
+        let error = if self.login_required {
+            let err = item_login_required();
+            json!({
+                "error_type": err.error_type,
+                "error_code": err.error_code,
+                "error_message": err.error_message,
+                "display_message": null,
+            })
+        } else {
+            Value::Null
+        };
+
+        json!({
+            "available_products": self.available_products,
+            "billed_products": self.billed_products,
+            "error": error,
+            "institution_id": self.institution_id,
+            "item_id": self.item_id,
+            "webhook": self.webhook,
+        })
+    }
+}
+
+impl Fixtures {
+    fn load() -> Fixtures {
+        Fixtures {
+            accounts: parse(ACCOUNTS),
+            balances: parse(BALANCES),
+            auth: parse(AUTH),
+            categories: parse(CATEGORIES),
+            holdings: parse(HOLDINGS),
+            identity: parse(IDENTITY),
+            income: parse(INCOME),
+            institutions: parse(INSTITUTIONS)["institutions"]
+                .as_array()
+                .cloned()
+                .unwrap_or_default(),
+            investment_transactions: parse(INVESTMENT_TRANSACTIONS),
+            liabilities: parse(LIABILITIES),
+            transactions: parse(TRANSACTIONS),
+        }
+    }
+}
+
+fn item_login_required() -> MockError {
+    MockError::new(
+        400,
+        "ITEM_ERROR",
+        "ITEM_LOGIN_REQUIRED",
+        "the login details of this item have changed (credentials, MFA, or required user action) and a user login is required to update this information",
+    )
+}
+
+fn parse(fixture: &str) -> Value {
+    serde_json::from_str(fixture).expect("fixtures are valid JSON")
+}
+
+fn str_of<'v>(value: &'v Value, field: &str) -> &'v str {
+    value[field].as_str().unwrap_or_default()
+}
+
+fn strings_of(value: &Value) -> Vec<String> {
+    value
+        .as_array()
+        .map(|values| {
+            values
+                .iter()
+                .filter_map(|value| value.as_str().map(String::from))
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+fn required_str<'v>(body: &'v Value, field: &str) -> Result<&'v str, MockError> {
+    match body[field].as_str() {
+        Some(value) if !value.is_empty() => Ok(value),
+        _ => Err(MockError::missing(field)),
+    }
+}
+
+fn account_ids_of(body: &Value) -> Option<Vec<String>> {
+    match body["options"]["account_ids"] {
+        Value::Array(_) => Some(strings_of(&body["options"]["account_ids"])),
+        _ => None,
+    }
+}
+
+fn has_account(accounts: &Value, account_id: &str) -> bool {
+    accounts
+        .as_array()
+        .into_iter()
+        .flatten()
+        .any(|account| str_of(account, "account_id") == account_id)
+}
+
+// retain_accounts drops every entry of a list whose `account_id` is not in
+// `account_ids`.
+fn retain_accounts(list: &mut Value, account_ids: &[String]) {
+    if let Value::Array(ref mut entries) = *list {
+        entries.retain(|entry| {
+            account_ids
+                .iter()
+                .any(|account_id| account_id == str_of(entry, "account_id"))
+        });
+    }
+}
+
+fn random_string(len: usize) -> String {
+    rand::thread_rng()
+        .sample_iter(&Alphanumeric)
+        .take(len)
+        .collect()
+}
+
+fn request_id() -> String {
+    random_string(15)
+}
+
+fn uuid() -> String {
+    let mut rng = rand::thread_rng();
+    format!(
+        "{:08x}-{:04x}-4{:03x}-{:04x}-{:012x}",
+        rng.gen::<u32>(),
+        rng.gen::<u16>(),
+        rng.gen::<u16>() & 0xfff,
+        (rng.gen::<u16>() & 0x3fff) | 0x8000,
+        rng.gen::<u64>() & 0xffff_ffff_ffff,
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::accounts::Accounts;
+    use crate::auth::Auth;
+    use crate::categories::Categories;
+    use crate::environments::Environment;
+    use crate::errors::ErrorCode;
+    use crate::holdings::Holdings;
+    use crate::identities::Identities;
+    use crate::incomes::Incomes;
+    use crate::institutions::Institutions;
+    use crate::investment_transactions::InvestmentTransactions;
+    use crate::items::Items;
+    use crate::liabilities::Liabilities;
+    use crate::plaid::Client;
+    use crate::processors::Processors;
+    use crate::sandbox::Sandbox;
+    use crate::transactions::Transactions;
+
+    const ACCESS_TOKEN: &str = "access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6";
+    const CHECKING: &str = "BxBXxLj1m4HMXBm9WZZmCWVbPjX16EHwv99vp";
+
+    fn test_client(mock: &MockPlaid) -> Client {
+        Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(mock.clone())
+    }
+
+    #[test]
+    fn test_item_lifecycle() {
+        let mock = MockPlaid::new();
+        let client = test_client(&mock);
+
+        let public_token = client
+            .create_sandbox_public_token("ins_109508", &["transactions"])
+            .unwrap()
+            .public_token;
+        let exchanged = client.exchange_public_token(&public_token).unwrap();
+        let access_token = exchanged.access_token.as_str();
+
+        let err = client.exchange_public_token(&public_token).err().unwrap();
+        assert_eq!(Some(&ErrorCode::InvalidPublicToken), err.error_code());
+
+        let item = client.get_item(access_token).unwrap().item;
+        assert_eq!(exchanged.item_id, item.item_id);
+        assert_eq!(vec!["transactions"], item.billed_products);
+
+        let transactions = client
+            .get_transactions(access_token, "2019-08-01", "2019-08-31")
+            .unwrap();
+        assert_eq!(12, transactions.total_transactions);
+
+        client.get_liabilities(access_token).unwrap();
+        let item = client.get_item(access_token).unwrap().item;
+        assert_eq!(vec!["liabilities", "transactions"], item.billed_products);
+
+        assert!(client.reset_sandbox_item(access_token).unwrap().reset_login);
+        let err = client.get_accounts(access_token).err().unwrap();
+        assert_eq!(Some(&ErrorCode::ItemLoginRequired), err.error_code());
+
+        let update_token = client
+            .create_public_token(access_token)
+            .unwrap()
+            .public_token;
+        client.exchange_public_token(&update_token).unwrap();
+        client.get_accounts(access_token).unwrap();
+
+        let new_access_token = client
+            .invalidate_access_token(access_token)
+            .unwrap()
+            .new_access_token;
+        let err = client.get_item(access_token).err().unwrap();
+        assert_eq!(Some(&ErrorCode::InvalidAccessToken), err.error_code());
+
+        assert!(client.remove_item(&new_access_token).unwrap().removed);
+        let err = client.get_item(&new_access_token).err().unwrap();
+        assert_eq!(Some(&ErrorCode::InvalidAccessToken), err.error_code());
+    }
+
+    #[test]
+    fn test_serves_fixtures() {
+        let mock = MockPlaid::new();
+        let client = test_client(&mock);
+
+        assert_eq!(8, client.get_accounts(ACCESS_TOKEN).unwrap().accounts.len());
+        assert_eq!(8, client.get_balances(ACCESS_TOKEN).unwrap().accounts.len());
+        client.get_auth(ACCESS_TOKEN).unwrap();
+        client.get_identity(ACCESS_TOKEN).unwrap();
+        client.get_income(ACCESS_TOKEN).unwrap();
+        client.get_holdings(ACCESS_TOKEN).unwrap();
+        client.get_liabilities(ACCESS_TOKEN).unwrap();
+        client.get_categories().unwrap();
+        let investment_transactions = client
+            .get_investment_transactions(ACCESS_TOKEN, "2019-08-01", "2019-08-31")
+            .unwrap();
+        assert_eq!(4, investment_transactions.total_investment_transactions);
+
+        let institutions = client.get_institutions(2, 0).unwrap();
+        assert_eq!(6, institutions.total);
+        assert_eq!(2, institutions.institutions.len());
+        let institution = client.get_institution_by_id("ins_109511").unwrap();
+        assert_eq!("Tartan Bank", institution.institution.name);
+        let found = client
+            .search_institutions("bank", vec!["liabilities"])
+            .unwrap();
+        assert_eq!(2, found.institutions.len());
+
+        assert!(client
+            .create_stripe_token(ACCESS_TOKEN, CHECKING)
+            .unwrap()
+            .stripe_bank_account_token
+            .starts_with("btok_"));
+        let err = client
+            .create_apex_token(ACCESS_TOKEN, "not-an-account")
+            .err()
+            .unwrap();
+        assert_eq!(Some(&ErrorCode::InvalidAccountId), err.error_code());
+    }
+
+    #[test]
+    fn test_transactions_pages() {
+        let mock = MockPlaid::new();
+        let body = json!({
+            "client_id": "client_id",
+            "secret": "secret",
+            "access_token": ACCESS_TOKEN,
+            "start_date": "2019-08-12",
+            "end_date": "2019-08-31",
+            "options": {"account_ids": [CHECKING], "count": 3, "offset": 3},
+        });
+
+        let response = mock.handle("/transactions/get", &body.to_string());
+        let value: Value = serde_json::from_slice(&response.body).unwrap();
+
+        assert_eq!(200, response.status);
+        assert_eq!(6, value["total_transactions"]);
+        assert_eq!(1, value["accounts"].as_array().unwrap().len());
+        let dates: Vec<&str> = value["transactions"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|transaction| str_of(transaction, "date"))
+            .collect();
+        assert_eq!(vec!["2019-08-14", "2019-08-13", "2019-08-12"], dates);
+    }
+
+    #[test]
+    fn test_credentials() {
+        let mock = MockPlaid::new().with_credentials("client_id", "right");
+        let client = Client::new("client_id", "wrong", "public_key", Environment::SANDBOX)
+            .with_transport(mock);
+
+        let err = client.get_item(ACCESS_TOKEN).err().unwrap();
+
+        assert_eq!(Some(&ErrorCode::InvalidApiKeys), err.error_code());
+        assert_eq!(Some(400), err.status());
+    }
+}
@@ -0,0 +1,104 @@
+use std::collections::{HashSet, VecDeque};
+
+/// The most items Plaid returns in a single page.
+pub const MAX_PAGE_SIZE: i64 = 500;
+
+// Pager tracks a walk over an offset-paginated endpoint. Plaid pages by
+// offset into a list that can change while it is walked, so items that move
+// across a page boundary can be returned twice; the pager drops any item it
+// has already handed out.
+pub(crate) struct Pager<T> {
+    pub(crate) page_size: i64,
+    offset: i64,
+    total: Option<i64>,
+    buffer: VecDeque<T>,
+    seen: HashSet<String>,
+    done: bool,
+}
+
+impl<T> Pager<T> {
+    pub(crate) fn new() -> Pager<T> {
+        Pager {
+            page_size: MAX_PAGE_SIZE,
+            offset: 0,
+            total: None,
+            buffer: VecDeque::new(),
+            seen: HashSet::new(),
+            done: false,
+        }
+    }
+
+    // start_at skips the first `offset` items, e.g. to resume an earlier walk.
+    pub(crate) fn start_at(&mut self, offset: i64) {
+        self.offset = offset;
+    }
+
+    // resume_offset returns the offset an interrupted walk can be resumed
+    // from: the position of the first item not yet handed out.
+    pub(crate) fn resume_offset(&self) -> i64 {
+        self.offset - self.buffer.len() as i64
+    }
+
+    // next_offset returns the offset of the next page to fetch, or `None` once
+    // every page has been fetched.
+    pub(crate) fn next_offset(&self) -> Option<i64> {
+        match self.total {
+            _ if self.done => None,
+            Some(total) if self.offset >= total => None,
+            _ => Some(self.offset),
+        }
+    }
+
+    pub(crate) fn is_first_page(&self) -> bool {
+        self.total.is_none()
+    }
+
+    pub(crate) fn total(&self) -> Option<i64> {
+        self.total
+    }
+
+    pub(crate) fn push_page<F>(&mut self, total: i64, items: Vec<T>, id: F)
+    where
+        F: Fn(&T) -> &str,
+    {
+        self.total = Some(total);
+        if items.is_empty() {
+            self.done = true;
+        }
+        self.offset += items.len() as i64;
+        for item in items {
+            if self.seen.insert(id(&item).to_string()) {
+                self.buffer.push_back(item);
+            }
+        }
+    }
+
+    pub(crate) fn pop(&mut self) -> Option<T> {
+        self.buffer.pop_front()
+    }
+
+    // finish stops the walk, e.g. after a page failed to load.
+    pub(crate) fn finish(&mut self) {
+        self.done = true;
+        self.buffer.clear();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pager_dedupes() {
+        let mut pager = Pager::new();
+        assert_eq!(Some(0), pager.next_offset());
+
+        pager.push_page(4, vec!["a", "b"], |item| item);
+        assert_eq!(Some(2), pager.next_offset());
+        pager.push_page(4, vec!["b", "c"], |item| item);
+        assert_eq!(None, pager.next_offset());
+
+        let items: Vec<&str> = std::iter::from_fn(|| pager.pop()).collect();
+        assert_eq!(vec!["a", "b", "c"], items);
+    }
+}
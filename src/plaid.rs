@@ -1,60 +1,1083 @@
+use crate::accounts::AccountsApi;
+use crate::assets::AssetsApi;
+use crate::auth::AuthApi;
+#[cfg(feature = "async")]
+use crate::cancel::CancellationToken;
+use crate::categories::CategoriesApi;
+use crate::endpoint::{self, Endpoint, RawEndpoint};
 use crate::environments::Environment;
-use crate::errors::{Error, Kind};
-use reqwest::Request;
+use crate::errors::{ApiError, Error, Kind};
+use crate::holdings::HoldingsApi;
+use crate::identities::IdentityApi;
+use crate::incomes::IncomeApi;
+use crate::institutions::InstitutionsApi;
+use crate::investment_transactions::InvestmentTransactionsApi;
+use crate::items::ItemsApi;
+use crate::liabilities::LiabilitiesApi;
+use crate::logging::LoggingMiddleware;
+use crate::middleware::{Chain, Middleware};
+use crate::processors::ProcessorsApi;
+use crate::rate_limit::{access_token_of, RateLimiter};
+use crate::retry::{self, RetryPolicy};
+use crate::sandbox::SandboxApi;
+use crate::timeouts::Timeouts;
+use crate::transactions::TransactionsApi;
+use crate::transport::{
+    normalize_endpoint, HttpRequest, HttpResponse, ReqwestTransport, StreamedResponse, Transport,
+};
+#[cfg(feature = "async")]
+use crate::transport::{AsyncReqwestTransport, AsyncTransport, TransportFuture};
+#[cfg(feature = "async")]
+use futures::{future, Future};
+use std::env;
+use std::io::{self, Read, Write};
+use std::sync::Arc;
+use std::thread;
+use std::time::{Duration, Instant};
 
-pub struct Client<'a> {
-    pub client_id: &'a str,
-    pub secret: &'a str,
-    pub public_key: &'a str,
-    pub environment: Environment,
-    pub http_client: reqwest::Client,
+/// Config is everything a client needs besides its transport. Clients share it
+/// behind an `Arc`, so cloning a client is cheap.
+#[derive(Clone)]
+struct Config {
+    client_id: String,
+    secret: String,
+    public_key: String,
+    environment: Environment,
+    user_agent: String,
+    api_version: String,
+    retry_policy: RetryPolicy,
+    rate_limiter: Option<RateLimiter>,
+    log_bodies: bool,
+    middleware: Vec<Arc<dyn Middleware>>,
+    timeouts: Timeouts,
+    call_timeout: Option<Duration>,
+    #[cfg(feature = "async")]
+    cancellation: Option<CancellationToken>,
 }
 
-impl<'a> Client<'a> {
+impl Config {
+    fn new(client_id: &str, secret: &str, public_key: &str, environment: Environment) -> Config {
+        Config {
+            client_id: client_id.to_string(),
+            secret: secret.to_string(),
+            public_key: public_key.to_string(),
+            environment,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            api_version: DEFAULT_API_VERSION.to_string(),
+            retry_policy: RetryPolicy::never(),
+            rate_limiter: None,
+            log_bodies: false,
+            middleware: Vec::new(),
+            timeouts: Timeouts::new(),
+            call_timeout: None,
+            #[cfg(feature = "async")]
+            cancellation: None,
+        }
+    }
+
+    // chain puts logging ahead of the configured middleware, so that it sees
+    // the final result of every attempt.
+    fn chain(&self) -> Chain {
+        let mut chain = Chain::default();
+        chain.push(Arc::new(
+            LoggingMiddleware::new().with_bodies(self.log_bodies),
+        ));
+        for layer in &self.middleware {
+            chain.push(layer.clone());
+        }
+        chain
+    }
+
+    // timeout_for prefers the timeout set for calls made through the client
+    // over the one configured for the endpoint.
+    fn timeout_for(&self, endpoint: &str) -> Duration {
+        self.call_timeout
+            .unwrap_or_else(|| self.timeouts.for_endpoint(endpoint))
+    }
+}
+
+const DEFAULT_USER_AGENT: &str = "Plaid Rust v0.0.1";
+const DEFAULT_API_VERSION: &str = "2019-05-29";
+
+/// Client makes blocking calls to Plaid. It owns its credentials and is cheap
+/// to clone, so one client can be kept in application state and shared across
+/// threads. Use `Client::builder` for anything beyond the defaults.
+#[derive(Clone)]
+pub struct Client {
+    config: Arc<Config>,
+    transport: Arc<dyn Transport>,
+}
+
+impl Client {
+    /// Creates a client that talks to Plaid over HTTPS with reqwest.
+    pub fn new(
+        client_id: &str,
+        secret: &str,
+        public_key: &str,
+        environment: Environment,
+    ) -> Client {
+        Client {
+            config: Arc::new(Config::new(client_id, secret, public_key, environment)),
+            transport: Arc::new(ReqwestTransport::new()),
+        }
+    }
+
+    /// Starts building a client.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.config.client_id
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.config.secret
+    }
+
+    pub fn public_key(&self) -> &str {
+        &self.config.public_key
+    }
+
+    pub fn environment(&self) -> &Environment {
+        &self.config.environment
+    }
+
+    /// Returns the policy failed calls are retried with.
+    pub fn retry_policy(&self) -> &RetryPolicy {
+        &self.config.retry_policy
+    }
+
+    /// Returns the rate limiter calls are throttled through, if any.
+    pub fn rate_limiter(&self) -> Option<&RateLimiter> {
+        self.config.rate_limiter.as_ref()
+    }
+
+    pub fn accounts(&self) -> AccountsApi<'_, Client> {
+        AccountsApi { client: self }
+    }
+
+    pub fn assets(&self) -> AssetsApi<'_, Client> {
+        AssetsApi { client: self }
+    }
+
+    pub fn auth(&self) -> AuthApi<'_, Client> {
+        AuthApi { client: self }
+    }
+
+    pub fn categories(&self) -> CategoriesApi<'_, Client> {
+        CategoriesApi { client: self }
+    }
+
+    pub fn holdings(&self) -> HoldingsApi<'_, Client> {
+        HoldingsApi { client: self }
+    }
+
+    pub fn identity(&self) -> IdentityApi<'_, Client> {
+        IdentityApi { client: self }
+    }
+
+    pub fn income(&self) -> IncomeApi<'_, Client> {
+        IncomeApi { client: self }
+    }
+
+    pub fn institutions(&self) -> InstitutionsApi<'_, Client> {
+        InstitutionsApi { client: self }
+    }
+
+    pub fn investment_transactions(&self) -> InvestmentTransactionsApi<'_, Client> {
+        InvestmentTransactionsApi { client: self }
+    }
+
+    pub fn items(&self) -> ItemsApi<'_, Client> {
+        ItemsApi { client: self }
+    }
+
+    pub fn liabilities(&self) -> LiabilitiesApi<'_, Client> {
+        LiabilitiesApi { client: self }
+    }
+
+    pub fn processors(&self) -> ProcessorsApi<'_, Client> {
+        ProcessorsApi { client: self }
+    }
+
+    pub fn sandbox(&self) -> SandboxApi<'_, Client> {
+        SandboxApi { client: self }
+    }
+
+    pub fn transactions(&self) -> TransactionsApi<'_, Client> {
+        TransactionsApi { client: self }
+    }
+
+    /// Replaces the transport every call is sent through.
+    pub fn with_transport<T: Transport + 'static>(mut self, transport: T) -> Client {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Sets the policy used to retry failed calls.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> Client {
+        Arc::make_mut(&mut self.config).retry_policy = retry_policy;
+        self
+    }
+
+    /// Throttles every call, including retries, through `rate_limiter`.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> Client {
+        Arc::make_mut(&mut self.config).rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Logs the redacted request and response bodies of every call at debug
+    /// level. Off by default.
+    pub fn with_body_logging(mut self, log_bodies: bool) -> Client {
+        Arc::make_mut(&mut self.config).log_bodies = log_bodies;
+        self
+    }
+
+    /// Adds a layer to the end of the middleware chain every call runs
+    /// through.
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> Client {
+        Arc::make_mut(&mut self.config)
+            .middleware
+            .push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets how long each attempt at a call may take, per endpoint.
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> Client {
+        Arc::make_mut(&mut self.config).timeouts = timeouts;
+        self
+    }
+
+    /// Overrides the timeout of every endpoint. Clients are cheap to clone, so
+    /// a single call can be given its own timeout with
+    /// `client.clone().with_timeout(timeout).get_accounts(..)`.
+    pub fn with_timeout(mut self, timeout: Duration) -> Client {
+        Arc::make_mut(&mut self.config).call_timeout = Some(timeout);
+        self
+    }
+
     pub fn call<T>(&self, endpoint: &str, body: &str) -> Result<T, Error>
     where
         for<'de> T: serde::de::Deserialize<'de>,
     {
-        let request = self.new_request(endpoint, body);
+        self.call_with(endpoint, body, decode_response)
+    }
+
+    /// Calls an endpoint that answers with something other than JSON, e.g. a
+    /// PDF, and returns the body as is. Error responses are still decoded.
+    pub fn call_raw(&self, endpoint: &str, body: &str) -> Result<Vec<u8>, Error> {
+        self.call_with(endpoint, body, decode_raw_response)
+    }
+
+    fn call_with<T>(
+        &self,
+        endpoint: &str,
+        body: &str,
+        decode: fn(HttpResponse) -> Result<T, Error>,
+    ) -> Result<T, Error> {
+        let config = &self.config;
+        let request = new_request(config, endpoint, body);
+        let access_token = access_token_of(body);
+        let chain = config.chain();
+
+        retry::retry(&config.retry_policy, || {
+            if let Some(ref rate_limiter) = config.rate_limiter {
+                thread::sleep(rate_limiter.acquire(&request.endpoint, access_token.as_deref()));
+            }
+
+            let mut request = request.clone();
+            let (entered, short_circuit) = chain.before(&mut request);
+
+            let started_at = Instant::now();
+            let mut result = match short_circuit {
+                Some(result) => result,
+                None => self.transport.send(request.clone()),
+            };
+            chain.after(entered, &request, &mut result, started_at.elapsed());
+
+            result.and_then(decode)
+        })
+    }
+
+    /// Sends `request` to its endpoint, adding the credentials the endpoint
+    /// expects.
+    pub fn send<E: Endpoint>(&self, request: &E) -> Result<E::Response, Error> {
+        let body = endpoint::encode(request, self.client_id(), self.secret(), self.public_key())?;
+        self.call(E::PATH, &body)
+    }
+
+    /// Sends `request` to a raw endpoint, adding the credentials the endpoint
+    /// expects, and returns the response body as is.
+    pub fn send_raw<E: RawEndpoint>(&self, request: &E) -> Result<Vec<u8>, Error> {
+        let body =
+            endpoint::encode_raw(request, self.client_id(), self.secret(), self.public_key())?;
+        self.call_raw(E::PATH, &body)
+    }
+
+    /// Sends `request` like `send_raw`, but copies the response body into
+    /// `writer` as it arrives and returns how many bytes were written. Only
+    /// getting the response is retried, not copying its body. Middleware and
+    /// body logging see whole responses, so with either the body is read in
+    /// full before it is written.
+    pub fn write_raw<E: RawEndpoint>(
+        &self,
+        request: &E,
+        writer: &mut dyn Write,
+    ) -> Result<u64, Error> {
+        let body =
+            endpoint::encode_raw(request, self.client_id(), self.secret(), self.public_key())?;
+        if self.config.log_bodies || !self.config.middleware.is_empty() {
+            let body = self.call_raw(E::PATH, &body)?;
+            writer
+                .write_all(&body)
+                .map_err(|err| Error::new(Kind::Io(err)))?;
+            return Ok(body.len() as u64);
+        }
+
+        let mut response = self.call_streamed(E::PATH, &body)?;
+        io::copy(&mut response.body, writer).map_err(|err| Error::new(Kind::Io(err)))
+    }
+
+    // call_streamed is `call_with` for responses whose body is left unread.
+    // Error responses are read in full and decoded.
+    fn call_streamed(&self, endpoint: &str, body: &str) -> Result<StreamedResponse, Error> {
+        let config = &self.config;
+        let request = new_request(config, endpoint, body);
+        let access_token = access_token_of(body);
+        let chain = config.chain();
+
+        retry::retry(&config.retry_policy, || {
+            if let Some(ref rate_limiter) = config.rate_limiter {
+                thread::sleep(rate_limiter.acquire(&request.endpoint, access_token.as_deref()));
+            }
+
+            let mut request = request.clone();
+            let (entered, short_circuit) = chain.before(&mut request);
+
+            let started_at = Instant::now();
+            let streamed = match short_circuit {
+                Some(result) => result.map(StreamedResponse::from),
+                None => self.transport.send_streamed(request.clone()),
+            };
+
+            match streamed {
+                // The body is left to the caller, so the chain only sees the
+                // status and headers.
+                Ok(streamed) if is_success(streamed.status) => {
+                    let mut result = Ok(HttpResponse {
+                        status: streamed.status,
+                        headers: streamed.headers.clone(),
+                        body: Vec::new(),
+                    });
+                    chain.after(entered, &request, &mut result, started_at.elapsed());
+                    result.map(|_| streamed)
+                }
+                streamed => {
+                    let mut result = streamed.and_then(read_streamed);
+                    chain.after(entered, &request, &mut result, started_at.elapsed());
+                    result.and_then(|response| {
+                        if !is_success(response.status) {
+                            return Err(decode_error(response.status, response.text()));
+                        }
+                        Ok(StreamedResponse::from(response))
+                    })
+                }
+            }
+        })
+    }
+}
+
+/// ClientBuilder configures a `Client` or `AsyncClient`. Credentials can be
+/// given directly or read from the `PLAID_CLIENT_ID`, `PLAID_SECRET`,
+/// `PLAID_PUBLIC_KEY` and `PLAID_ENV` environment variables with `from_env`.
+/// The environment defaults to the sandbox.
+#[derive(Clone)]
+pub struct ClientBuilder {
+    config: Config,
+    connect_timeout: Option<Duration>,
+}
+
+impl Default for ClientBuilder {
+    fn default() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+}
+
+impl ClientBuilder {
+    pub fn new() -> ClientBuilder {
+        ClientBuilder {
+            config: Config::new("", "", "", Environment::SANDBOX),
+            connect_timeout: None,
+        }
+    }
+
+    /// Reads whichever of `PLAID_CLIENT_ID`, `PLAID_SECRET`, `PLAID_PUBLIC_KEY`
+    /// and `PLAID_ENV` are set. Fails if `PLAID_ENV` is not a valid
+    /// environment.
+    pub fn from_env(self) -> Result<ClientBuilder, Error> {
+        self.from_env_with(|name| env::var(name).ok())
+    }
+
+    /// Like `from_env`, but looks the variables up with `lookup` instead of
+    /// reading the process environment.
+    pub fn from_env_with<F>(mut self, lookup: F) -> Result<ClientBuilder, Error>
+    where
+        F: Fn(&str) -> Option<String>,
+    {
+        if let Some(client_id) = lookup("PLAID_CLIENT_ID") {
+            self.config.client_id = client_id;
+        }
+        if let Some(secret) = lookup("PLAID_SECRET") {
+            self.config.secret = secret;
+        }
+        if let Some(public_key) = lookup("PLAID_PUBLIC_KEY") {
+            self.config.public_key = public_key;
+        }
+        if let Some(environment) = lookup("PLAID_ENV") {
+            self.config.environment = environment.parse()?;
+        }
+        Ok(self)
+    }
+
+    pub fn with_client_id(mut self, client_id: &str) -> ClientBuilder {
+        self.config.client_id = client_id.to_string();
+        self
+    }
+
+    pub fn with_secret(mut self, secret: &str) -> ClientBuilder {
+        self.config.secret = secret.to_string();
+        self
+    }
+
+    pub fn with_public_key(mut self, public_key: &str) -> ClientBuilder {
+        self.config.public_key = public_key.to_string();
+        self
+    }
+
+    pub fn with_environment(mut self, environment: Environment) -> ClientBuilder {
+        self.config.environment = environment;
+        self
+    }
+
+    /// Bounds how long an attempt at a call may take, from connecting to
+    /// reading the last byte of the response, for endpoints without a timeout
+    /// of their own.
+    pub fn with_timeout(mut self, timeout: Duration) -> ClientBuilder {
+        self.config.timeouts = self.config.timeouts.with_default(timeout);
+        self
+    }
+
+    /// Sets how long each attempt at a call may take, per endpoint.
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> ClientBuilder {
+        self.config.timeouts = timeouts;
+        self
+    }
+
+    /// Bounds how long connecting to Plaid may take.
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> ClientBuilder {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    pub fn with_user_agent(mut self, user_agent: &str) -> ClientBuilder {
+        self.config.user_agent = user_agent.to_string();
+        self
+    }
+
+    /// Sets the `Plaid-Version` header sent with every call.
+    pub fn with_api_version(mut self, api_version: &str) -> ClientBuilder {
+        self.config.api_version = api_version.to_string();
+        self
+    }
+
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> ClientBuilder {
+        self.config.retry_policy = retry_policy;
+        self
+    }
+
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> ClientBuilder {
+        self.config.rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    pub fn with_body_logging(mut self, log_bodies: bool) -> ClientBuilder {
+        self.config.log_bodies = log_bodies;
+        self
+    }
 
-        request.and_then(|req| self.execute_request::<T>(req))
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> ClientBuilder {
+        self.config.middleware.push(Arc::new(middleware));
+        self
     }
 
-    fn new_request(&self, endpoint: &str, body: &str) -> Result<Request, Error> {
-        let mut path = endpoint.to_string();
+    /// Builds a blocking client. Fails if the client ID or secret is missing.
+    pub fn build(self) -> Result<Client, Error> {
+        self.validate()?;
 
-        if !endpoint.starts_with("/") {
-            path.insert(0, '/');
+        let mut transport = ReqwestTransport::new();
+        if let Some(connect_timeout) = self.connect_timeout {
+            transport = transport.with_connect_timeout(connect_timeout);
         }
 
-        let mut url = "https://".to_string();
-        url.push_str(self.environment.host());
-        url.push_str(path.as_str());
+        Ok(Client {
+            config: Arc::new(self.config),
+            transport: Arc::new(transport),
+        })
+    }
 
-        let request = self
-            .http_client
-            .post(url.as_str())
-            .body(body.to_string())
-            .header("Content-Type", "application/json")
-            .header("User-Agent", "Plaid Rust v0.0.1")
-            .header("Plaid-Version", "2019-05-29");
+    /// Builds a non-blocking client. Fails if the client ID or secret is
+    /// missing.
+    #[cfg(feature = "async")]
+    pub fn build_async(self) -> Result<AsyncClient, Error> {
+        self.validate()?;
 
-        request
+        let mut http_client = reqwest::r#async::Client::builder();
+        if let Some(connect_timeout) = self.connect_timeout {
+            http_client = http_client.connect_timeout(connect_timeout);
+        }
+        let http_client = http_client
             .build()
-            .map_err(|err| Error::new(Kind::Reqwest(err)))
+            .map_err(|err| Error::new(Kind::Reqwest(err)))?;
+
+        Ok(AsyncClient {
+            config: Arc::new(self.config),
+            transport: Arc::new(AsyncReqwestTransport::from(http_client)),
+        })
+    }
+
+    fn validate(&self) -> Result<(), Error> {
+        if self.config.client_id.is_empty() {
+            return Err(Error::new(Kind::ValidationError(
+                "client_id must be specified",
+            )));
+        }
+        if self.config.secret.is_empty() {
+            return Err(Error::new(Kind::ValidationError(
+                "secret must be specified",
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// A boxed future resolving to a Plaid response, returned by every method of
+/// the async product traits.
+#[cfg(feature = "async")]
+pub type ResponseFuture<T> = Box<dyn Future<Item = T, Error = Error> + Send>;
+
+/// AsyncClient is the non-blocking counterpart of `Client`. Its product traits
+/// (`AsyncItems`, `AsyncAuth`, ...) take the same arguments and resolve to the
+/// same response types, but return futures that must be driven by a tokio
+/// runtime.
+#[cfg(feature = "async")]
+#[derive(Clone)]
+pub struct AsyncClient {
+    config: Arc<Config>,
+    transport: Arc<dyn AsyncTransport>,
+}
+
+#[cfg(feature = "async")]
+impl AsyncClient {
+    /// Creates a client that talks to Plaid over HTTPS with reqwest.
+    pub fn new(
+        client_id: &str,
+        secret: &str,
+        public_key: &str,
+        environment: Environment,
+    ) -> AsyncClient {
+        AsyncClient {
+            config: Arc::new(Config::new(client_id, secret, public_key, environment)),
+            transport: Arc::new(AsyncReqwestTransport::new()),
+        }
+    }
+
+    /// Starts building a client; finish with `ClientBuilder::build_async`.
+    pub fn builder() -> ClientBuilder {
+        ClientBuilder::new()
+    }
+
+    pub fn client_id(&self) -> &str {
+        &self.config.client_id
+    }
+
+    pub fn secret(&self) -> &str {
+        &self.config.secret
+    }
+
+    pub fn public_key(&self) -> &str {
+        &self.config.public_key
+    }
+
+    pub fn environment(&self) -> &Environment {
+        &self.config.environment
+    }
+
+    pub fn accounts(&self) -> AccountsApi<'_, AsyncClient> {
+        AccountsApi { client: self }
+    }
+
+    pub fn assets(&self) -> AssetsApi<'_, AsyncClient> {
+        AssetsApi { client: self }
+    }
+
+    pub fn auth(&self) -> AuthApi<'_, AsyncClient> {
+        AuthApi { client: self }
+    }
+
+    pub fn categories(&self) -> CategoriesApi<'_, AsyncClient> {
+        CategoriesApi { client: self }
+    }
+
+    pub fn holdings(&self) -> HoldingsApi<'_, AsyncClient> {
+        HoldingsApi { client: self }
+    }
+
+    pub fn identity(&self) -> IdentityApi<'_, AsyncClient> {
+        IdentityApi { client: self }
+    }
+
+    pub fn income(&self) -> IncomeApi<'_, AsyncClient> {
+        IncomeApi { client: self }
+    }
+
+    pub fn institutions(&self) -> InstitutionsApi<'_, AsyncClient> {
+        InstitutionsApi { client: self }
+    }
+
+    pub fn investment_transactions(&self) -> InvestmentTransactionsApi<'_, AsyncClient> {
+        InvestmentTransactionsApi { client: self }
+    }
+
+    pub fn items(&self) -> ItemsApi<'_, AsyncClient> {
+        ItemsApi { client: self }
+    }
+
+    pub fn liabilities(&self) -> LiabilitiesApi<'_, AsyncClient> {
+        LiabilitiesApi { client: self }
     }
 
-    fn execute_request<T>(&self, request: Request) -> Result<T, Error>
+    pub fn processors(&self) -> ProcessorsApi<'_, AsyncClient> {
+        ProcessorsApi { client: self }
+    }
+
+    pub fn sandbox(&self) -> SandboxApi<'_, AsyncClient> {
+        SandboxApi { client: self }
+    }
+
+    pub fn transactions(&self) -> TransactionsApi<'_, AsyncClient> {
+        TransactionsApi { client: self }
+    }
+
+    /// Replaces the transport every call is sent through.
+    pub fn with_transport<T: AsyncTransport + 'static>(mut self, transport: T) -> AsyncClient {
+        self.transport = Arc::new(transport);
+        self
+    }
+
+    /// Sets the policy used to retry failed calls.
+    pub fn with_retry_policy(mut self, retry_policy: RetryPolicy) -> AsyncClient {
+        Arc::make_mut(&mut self.config).retry_policy = retry_policy;
+        self
+    }
+
+    /// Throttles every call, including retries, through `rate_limiter`.
+    pub fn with_rate_limiter(mut self, rate_limiter: RateLimiter) -> AsyncClient {
+        Arc::make_mut(&mut self.config).rate_limiter = Some(rate_limiter);
+        self
+    }
+
+    /// Logs the redacted request and response bodies of every call at debug
+    /// level. Off by default.
+    pub fn with_body_logging(mut self, log_bodies: bool) -> AsyncClient {
+        Arc::make_mut(&mut self.config).log_bodies = log_bodies;
+        self
+    }
+
+    /// Adds a layer to the end of the middleware chain every call runs
+    /// through.
+    pub fn with_middleware<M: Middleware + 'static>(mut self, middleware: M) -> AsyncClient {
+        Arc::make_mut(&mut self.config)
+            .middleware
+            .push(Arc::new(middleware));
+        self
+    }
+
+    /// Sets how long each attempt at a call may take, per endpoint.
+    pub fn with_timeouts(mut self, timeouts: Timeouts) -> AsyncClient {
+        Arc::make_mut(&mut self.config).timeouts = timeouts;
+        self
+    }
+
+    /// Overrides the timeout of every endpoint. Clients are cheap to clone, so
+    /// a single call can be given its own timeout with
+    /// `client.clone().with_timeout(timeout).get_accounts(..)`.
+    pub fn with_timeout(mut self, timeout: Duration) -> AsyncClient {
+        Arc::make_mut(&mut self.config).call_timeout = Some(timeout);
+        self
+    }
+
+    /// Cancels the calls made through the client once `token` is cancelled.
+    pub fn with_cancellation(mut self, token: CancellationToken) -> AsyncClient {
+        Arc::make_mut(&mut self.config).cancellation = Some(token);
+        self
+    }
+
+    pub fn call<T>(&self, endpoint: &str, body: &str) -> ResponseFuture<T>
     where
-        for<'de> T: serde::de::Deserialize<'de>,
+        for<'de> T: serde::de::Deserialize<'de> + Send + 'static,
+    {
+        self.call_with(endpoint, body, decode_response)
+    }
+
+    /// Calls an endpoint that answers with something other than JSON, e.g. a
+    /// PDF, and returns the body as is. Error responses are still decoded.
+    pub fn call_raw(&self, endpoint: &str, body: &str) -> ResponseFuture<Vec<u8>> {
+        self.call_with(endpoint, body, decode_raw_response)
+    }
+
+    fn call_with<T>(
+        &self,
+        endpoint: &str,
+        body: &str,
+        decode: fn(HttpResponse) -> Result<T, Error>,
+    ) -> ResponseFuture<T>
+    where
+        T: Send + 'static,
     {
-        self.http_client
-            .execute(request)
-            .map_err(|err| Error::new(Kind::Reqwest(err)))
-            .and_then(|mut res| {
-                let text = res.text().unwrap();
-                println!("{:?}", text);
-                serde_json::from_str(text.as_str()).map_err(|err| Error::new(Kind::Json(err)))
-            })
+        let request = new_request(&self.config, endpoint, body);
+        let access_token = access_token_of(body);
+        let transport = self.transport.clone();
+        let rate_limiter = self.config.rate_limiter.clone();
+        let chain = self.config.chain();
+
+        let call = Box::new(retry::retry_async(
+            self.config.retry_policy.clone(),
+            move || {
+                let wait = match rate_limiter {
+                    Some(ref rate_limiter) => {
+                        rate_limiter.acquire(&request.endpoint, access_token.as_deref())
+                    }
+                    None => Default::default(),
+                };
+                let transport = transport.clone();
+                let chain = chain.clone();
+                let mut request = request.clone();
+
+                tokio_timer::Delay::new(Instant::now() + wait)
+                    .then(move |_| -> TransportFuture {
+                        let (entered, short_circuit) = chain.before(&mut request);
+                        let started_at = Instant::now();
+                        let response = match short_circuit {
+                            Some(result) => Box::new(future::result(result)),
+                            None => with_timeout(transport.send(request.clone()), request.timeout),
+                        };
+
+                        Box::new(response.then(move |mut result| {
+                            chain.after(entered, &request, &mut result, started_at.elapsed());
+                            result
+                        }))
+                    })
+                    .and_then(decode)
+            },
+        ));
+
+        match self.config.cancellation {
+            Some(ref token) => with_cancellation(call, token),
+            None => call,
+        }
+    }
+
+    /// Sends `request` to its endpoint, adding the credentials the endpoint
+    /// expects.
+    pub fn send<E: Endpoint>(&self, request: &E) -> ResponseFuture<E::Response> {
+        match endpoint::encode(request, self.client_id(), self.secret(), self.public_key()) {
+            Ok(body) => self.call(E::PATH, &body),
+            Err(err) => Box::new(future::err(err)),
+        }
+    }
+
+    /// Sends `request` to a raw endpoint, adding the credentials the endpoint
+    /// expects, and returns the response body as is.
+    pub fn send_raw<E: RawEndpoint>(&self, request: &E) -> ResponseFuture<Vec<u8>> {
+        match endpoint::encode_raw(request, self.client_id(), self.secret(), self.public_key()) {
+            Ok(body) => self.call_raw(E::PATH, &body),
+            Err(err) => Box::new(future::err(err)),
+        }
+    }
+}
+
+// with_timeout fails `response` with a timeout error once `timeout` has
+// passed.
+#[cfg(feature = "async")]
+fn with_timeout(response: TransportFuture, timeout: Option<Duration>) -> TransportFuture {
+    let timeout = match timeout {
+        Some(timeout) => timeout,
+        None => return response,
+    };
+
+    Box::new(
+        tokio_timer::Timeout::new(response, timeout).map_err(move |err| {
+            if err.is_elapsed() {
+                return Error::new(Kind::Timeout(timeout));
+            }
+            match err.into_inner() {
+                Some(err) => err,
+                None => Error::new(Kind::Transport("timer failed".to_string())),
+            }
+        }),
+    )
+}
+
+// with_cancellation fails `call` as soon as `token` is cancelled, dropping
+// whatever attempt or wait it was in.
+#[cfg(feature = "async")]
+fn with_cancellation<T>(call: ResponseFuture<T>, token: &CancellationToken) -> ResponseFuture<T>
+where
+    T: Send + 'static,
+{
+    if token.is_cancelled() {
+        return fail(Kind::Cancelled);
+    }
+
+    Box::new(call.select2(token.cancelled()).then(|result| match result {
+        Ok(future::Either::A((item, _))) => Ok(item),
+        Err(future::Either::A((err, _))) => Err(err),
+        Ok(future::Either::B(_)) | Err(future::Either::B(_)) => Err(Error::new(Kind::Cancelled)),
+    }))
+}
+
+/// Fails an async call before anything is sent, e.g. when validating input.
+#[cfg(feature = "async")]
+pub(crate) fn fail<T>(kind: Kind) -> ResponseFuture<T>
+where
+    T: Send + 'static,
+{
+    Box::new(future::err(Error::new(kind)))
+}
+
+fn new_request(config: &Config, endpoint: &str, body: &str) -> HttpRequest {
+    let path = normalize_endpoint(endpoint);
+
+    let mut url = config.environment.base_url();
+    url.push_str(path.as_str());
+    let timeout = config.timeout_for(&path);
+
+    HttpRequest {
+        url,
+        endpoint: path,
+        headers: vec![
+            ("Content-Type".to_string(), "application/json".to_string()),
+            ("User-Agent".to_string(), config.user_agent.clone()),
+            ("Plaid-Version".to_string(), config.api_version.clone()),
+        ],
+        body: body.to_string(),
+        timeout: Some(timeout),
+    }
+}
+
+fn decode_response<T>(response: HttpResponse) -> Result<T, Error>
+where
+    for<'de> T: serde::de::Deserialize<'de>,
+{
+    let text = response.text();
+
+    if !is_success(response.status) {
+        return Err(decode_error(response.status, text));
+    }
+
+    serde_json::from_str(text.as_str()).map_err(|err| Error::new(Kind::Json(err)))
+}
+
+fn decode_raw_response(response: HttpResponse) -> Result<Vec<u8>, Error> {
+    if !is_success(response.status) {
+        return Err(decode_error(response.status, response.text()));
+    }
+
+    Ok(response.body)
+}
+
+// read_streamed reads the whole body of `streamed`.
+fn read_streamed(mut streamed: StreamedResponse) -> Result<HttpResponse, Error> {
+    let mut body = Vec::new();
+    streamed
+        .body
+        .read_to_end(&mut body)
+        .map_err(|err| Error::new(Kind::Io(err)))?;
+
+    Ok(HttpResponse {
+        status: streamed.status,
+        headers: streamed.headers,
+        body,
+    })
+}
+
+fn is_success(status: u16) -> bool {
+    (200..300).contains(&status)
+}
+
+// decode_error turns a non-2xx response into a typed API error when the body
+// is a Plaid error, or a bare status error otherwise.
+fn decode_error(status: u16, body: String) -> Error {
+    match serde_json::from_str::<ApiError>(body.as_str()) {
+        Ok(mut api_error) => {
+            api_error.status = status;
+            Error::new(Kind::Api(api_error))
+        }
+        Err(_) => Error::new(Kind::Status(status, body)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::items::Items;
+    use crate::transport::InMemoryTransport;
+
+    const REMOVE_ITEM: &str = r#"{"request_id": "m8MDnv9okwxFNBV", "removed": true}"#;
+
+    fn assert_send_sync<T: Send + Sync + Clone + 'static>() {}
+
+    #[test]
+    fn test_client_is_send_sync() {
+        assert_send_sync::<Client>();
+        #[cfg(feature = "async")]
+        assert_send_sync::<AsyncClient>();
+    }
+
+    #[test]
+    fn test_builder() {
+        let transport = InMemoryTransport::new().with_response("/item/remove", REMOVE_ITEM);
+        let test_client = Client::builder()
+            .with_client_id("client_id")
+            .with_secret("secret")
+            .with_environment(Environment::DEVELOPMENT)
+            .with_timeout(Duration::from_secs(5))
+            .with_user_agent("my-app/1.0")
+            .with_api_version("2020-09-14")
+            .build()
+            .unwrap()
+            .with_transport(transport.clone());
+
+        let shared = test_client.clone();
+        std::thread::spawn(move || shared.remove_item("access-sandbox-token").unwrap())
+            .join()
+            .unwrap();
+
+        let request = &transport.requests()[0];
+        assert_eq!("https://development.plaid.com/item/remove", request.url);
+        assert!(request
+            .headers
+            .contains(&("User-Agent".to_string(), "my-app/1.0".to_string())));
+        assert!(request
+            .headers
+            .contains(&("Plaid-Version".to_string(), "2020-09-14".to_string())));
+    }
+
+    #[test]
+    fn test_builder_requires_credentials() {
+        assert!(Client::builder()
+            .with_client_id("client_id")
+            .build()
+            .is_err());
+        assert!(Client::builder().with_secret("secret").build().is_err());
+    }
+
+    #[test]
+    fn test_builder_from_env() {
+        let lookup = |name: &str| match name {
+            "PLAID_CLIENT_ID" => Some("env_client_id".to_string()),
+            "PLAID_SECRET" => Some("env_secret".to_string()),
+            "PLAID_ENV" => Some("production".to_string()),
+            _ => None,
+        };
+
+        let test_client = Client::builder()
+            .from_env_with(lookup)
+            .unwrap()
+            .build()
+            .unwrap();
+
+        assert_eq!("env_client_id", test_client.client_id());
+        assert_eq!("env_secret", test_client.secret());
+        assert_eq!(&Environment::PRODUCTION, test_client.environment());
+    }
+
+    #[test]
+    fn test_timeouts() {
+        let transport = InMemoryTransport::new().with_response("/item/remove", REMOVE_ITEM);
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+            .with_timeouts(Timeouts::new().with_endpoint("/item/remove", Duration::from_secs(5)));
+
+        test_client.remove_item("access-sandbox-token").unwrap();
+        test_client
+            .clone()
+            .with_timeout(Duration::from_secs(1))
+            .remove_item("access-sandbox-token")
+            .unwrap();
+
+        let requests = transport.requests();
+        assert_eq!(Some(Duration::from_secs(5)), requests[0].timeout);
+        assert_eq!(Some(Duration::from_secs(1)), requests[1].timeout);
+    }
+
+    #[cfg(feature = "async")]
+    struct Stalled;
+
+    #[cfg(feature = "async")]
+    impl AsyncTransport for Stalled {
+        fn send(&self, _request: HttpRequest) -> TransportFuture {
+            Box::new(future::empty())
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_timeout() {
+        use crate::items::AsyncItems;
+
+        let test_client =
+            AsyncClient::new("client_id", "secret", "public_key", Environment::SANDBOX)
+                .with_transport(Stalled)
+                .with_timeout(Duration::from_millis(20));
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let err = runtime
+            .block_on(test_client.remove_item("access-sandbox-token"))
+            .err()
+            .unwrap();
+
+        assert!(err.is_timeout());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_cancellation() {
+        use crate::items::AsyncItems;
+
+        let token = CancellationToken::new();
+        let test_client =
+            AsyncClient::new("client_id", "secret", "public_key", Environment::SANDBOX)
+                .with_transport(Stalled)
+                .with_cancellation(token.clone());
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let call = test_client.remove_item("access-sandbox-token");
+        let cancel =
+            tokio_timer::Delay::new(Instant::now() + Duration::from_millis(20)).then(move |_| {
+                token.cancel();
+                Ok::<(), ()>(())
+            });
+        runtime.spawn(cancel);
+        let err = runtime.block_on(call).err().unwrap();
+
+        assert!(err.is_cancelled());
+        assert!(test_client
+            .remove_item("access-sandbox-token")
+            .wait()
+            .err()
+            .unwrap()
+            .is_cancelled());
     }
 }
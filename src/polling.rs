@@ -0,0 +1,505 @@
+use crate::assets::{Assets, GetAssetReportResponse};
+use crate::errors::{Error, ErrorCode, Kind};
+use crate::investment_transactions::{GetInvestmentTransactionsResponse, InvestmentTransactions};
+use crate::items::{GetItemResponse, Items};
+use crate::plaid::Client;
+use crate::transactions::{GetTransactionsResponse, RefreshTransactionsResponse, Transactions};
+use chrono::{DateTime, Utc};
+use std::thread;
+use std::time::{Duration, Instant};
+#[cfg(feature = "async")]
+use {
+    crate::assets::AsyncAssets,
+    crate::investment_transactions::AsyncInvestmentTransactions,
+    crate::items::AsyncItems,
+    crate::plaid::{AsyncClient, ResponseFuture},
+    crate::transactions::AsyncTransactions,
+    futures::{future, Future},
+};
+
+/// Polling waits for products Plaid is still preparing. Right after a public
+/// token is exchanged, `/transactions/get` and `/investments/transactions/get`
+/// fail with `PRODUCT_NOT_READY` until the first pull from the institution is
+/// done, and `/asset_report/get` fails with it until the report is built.
+///
+/// Each helper repeats its call every `PollPolicy` interval for as long as
+/// Plaid reports `PRODUCT_NOT_READY`. If the product is still not ready once
+/// the deadline has passed, the helper fails with an error for which
+/// `is_not_ready` returns true. Any other error is returned right away.
+///
+/// `refresh_transactions_and_wait` asks Plaid for an on-demand transactions
+/// update and waits for the item's status to report a newer successful
+/// update, the point at which Plaid sends `DEFAULT_UPDATE` and the new data
+/// can be read with `get_transactions`. If none is reported by the deadline,
+/// it fails with an error for which `is_not_ready` returns true.
+pub trait Polling {
+    fn wait_for_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        policy: &PollPolicy,
+    ) -> Result<GetTransactionsResponse, Error>;
+    fn wait_for_investment_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        policy: &PollPolicy,
+    ) -> Result<GetInvestmentTransactionsResponse, Error>;
+    fn wait_for_asset_report(
+        &self,
+        asset_report_token: &str,
+        policy: &PollPolicy,
+    ) -> Result<GetAssetReportResponse, Error>;
+    fn refresh_transactions_and_wait(
+        &self,
+        access_token: &str,
+        policy: &PollPolicy,
+    ) -> Result<RefreshTransactionsResponse, Error>;
+}
+
+/// PollPolicy sets how often a product is polled, and for how long. The
+/// default polls every five seconds for up to two minutes.
+#[derive(Clone, Debug)]
+pub struct PollPolicy {
+    pub interval: Duration,
+    pub deadline: Duration,
+}
+
+impl Default for PollPolicy {
+    fn default() -> PollPolicy {
+        PollPolicy {
+            interval: Duration::from_secs(5),
+            deadline: Duration::from_secs(120),
+        }
+    }
+}
+
+impl PollPolicy {
+    pub fn new() -> PollPolicy {
+        PollPolicy::default()
+    }
+
+    /// Sets how long to wait between calls.
+    pub fn with_interval(mut self, interval: Duration) -> PollPolicy {
+        self.interval = interval;
+        self
+    }
+
+    /// Sets how long to keep polling before giving up.
+    pub fn with_deadline(mut self, deadline: Duration) -> PollPolicy {
+        self.deadline = deadline;
+        self
+    }
+
+    // next_poll returns how long to wait before polling again, or the error to
+    // fail with once the deadline leaves no room for another call.
+    fn next_poll(&self, started_at: Instant, err: Error) -> Result<Duration, Error> {
+        let waited = started_at.elapsed();
+        match err.api_error() {
+            Some(api_error)
+                if api_error.error_code == ErrorCode::ProductNotReady
+                    && waited + self.interval > self.deadline =>
+            {
+                Err(Error::new(Kind::NotReady(waited, Some(api_error.clone()))))
+            }
+            Some(api_error) if api_error.error_code == ErrorCode::ProductNotReady => {
+                Ok(self.interval)
+            }
+            _ => Err(err),
+        }
+    }
+
+    // next_check returns how long to wait before checking again for an
+    // update, or the error to fail with once the deadline has passed.
+    fn next_check(&self, started_at: Instant) -> Result<Duration, Error> {
+        let waited = started_at.elapsed();
+        if waited + self.interval > self.deadline {
+            return Err(Error::new(Kind::NotReady(waited, None)));
+        }
+        Ok(self.interval)
+    }
+}
+
+// last_transactions_update returns when Plaid last updated an item's
+// transactions.
+fn last_transactions_update(resp: &GetItemResponse) -> Option<DateTime<Utc>> {
+    resp.status
+        .as_ref()
+        .and_then(|status| status.transactions.as_ref())
+        .and_then(|transactions| transactions.last_successful_update)
+}
+
+// poll runs `call` until it returns anything but `PRODUCT_NOT_READY`, or the
+// policy's deadline passes.
+fn poll<T, F>(policy: &PollPolicy, mut call: F) -> Result<T, Error>
+where
+    F: FnMut() -> Result<T, Error>,
+{
+    let started_at = Instant::now();
+    loop {
+        match call() {
+            Err(err) => thread::sleep(policy.next_poll(started_at, err)?),
+            Ok(value) => return Ok(value),
+        }
+    }
+}
+
+impl Polling for Client {
+    fn wait_for_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        policy: &PollPolicy,
+    ) -> Result<GetTransactionsResponse, Error> {
+        poll(policy, || {
+            self.get_transactions(access_token, start_date, end_date)
+        })
+    }
+
+    fn wait_for_investment_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        policy: &PollPolicy,
+    ) -> Result<GetInvestmentTransactionsResponse, Error> {
+        poll(policy, || {
+            self.get_investment_transactions(access_token, start_date, end_date)
+        })
+    }
+
+    fn wait_for_asset_report(
+        &self,
+        asset_report_token: &str,
+        policy: &PollPolicy,
+    ) -> Result<GetAssetReportResponse, Error> {
+        poll(policy, || self.get_asset_report(asset_report_token))
+    }
+
+    fn refresh_transactions_and_wait(
+        &self,
+        access_token: &str,
+        policy: &PollPolicy,
+    ) -> Result<RefreshTransactionsResponse, Error> {
+        let before = last_transactions_update(&self.get_item(access_token)?);
+        let resp = self.refresh_transactions(access_token)?;
+
+        let started_at = Instant::now();
+        loop {
+            if last_transactions_update(&self.get_item(access_token)?) != before {
+                return Ok(resp);
+            }
+            thread::sleep(policy.next_check(started_at)?);
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncPolling {
+    fn wait_for_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        policy: &PollPolicy,
+    ) -> ResponseFuture<GetTransactionsResponse>;
+    fn wait_for_investment_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        policy: &PollPolicy,
+    ) -> ResponseFuture<GetInvestmentTransactionsResponse>;
+    fn wait_for_asset_report(
+        &self,
+        asset_report_token: &str,
+        policy: &PollPolicy,
+    ) -> ResponseFuture<GetAssetReportResponse>;
+    fn refresh_transactions_and_wait(
+        &self,
+        access_token: &str,
+        policy: &PollPolicy,
+    ) -> ResponseFuture<RefreshTransactionsResponse>;
+}
+
+// poll_async is the non-blocking counterpart of `poll`, waiting between calls
+// on the tokio timer instead of blocking the thread.
+#[cfg(feature = "async")]
+fn poll_async<T, F>(policy: &PollPolicy, call: F) -> ResponseFuture<T>
+where
+    T: Send + 'static,
+    F: Fn() -> ResponseFuture<T> + Send + 'static,
+{
+    let policy = policy.clone();
+    let started_at = Instant::now();
+    Box::new(future::loop_fn((), move |()| {
+        let policy = policy.clone();
+        call().then(
+            move |result| -> Box<dyn Future<Item = _, Error = Error> + Send> {
+                match result {
+                    Ok(value) => Box::new(future::ok(future::Loop::Break(value))),
+                    Err(err) => match policy.next_poll(started_at, err) {
+                        Ok(wait) => Box::new(
+                            tokio_timer::Delay::new(Instant::now() + wait)
+                                .then(|_| Ok(future::Loop::Continue(()))),
+                        ),
+                        Err(err) => Box::new(future::err(err)),
+                    },
+                }
+            },
+        )
+    }))
+}
+
+#[cfg(feature = "async")]
+impl AsyncPolling for AsyncClient {
+    fn wait_for_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        policy: &PollPolicy,
+    ) -> ResponseFuture<GetTransactionsResponse> {
+        let client = self.clone();
+        let (access_token, start_date, end_date) = (
+            access_token.to_string(),
+            start_date.to_string(),
+            end_date.to_string(),
+        );
+        poll_async(policy, move || {
+            client.get_transactions(&access_token, &start_date, &end_date)
+        })
+    }
+
+    fn wait_for_investment_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        policy: &PollPolicy,
+    ) -> ResponseFuture<GetInvestmentTransactionsResponse> {
+        let client = self.clone();
+        let (access_token, start_date, end_date) = (
+            access_token.to_string(),
+            start_date.to_string(),
+            end_date.to_string(),
+        );
+        poll_async(policy, move || {
+            client.get_investment_transactions(&access_token, &start_date, &end_date)
+        })
+    }
+
+    fn wait_for_asset_report(
+        &self,
+        asset_report_token: &str,
+        policy: &PollPolicy,
+    ) -> ResponseFuture<GetAssetReportResponse> {
+        let client = self.clone();
+        let asset_report_token = asset_report_token.to_string();
+        poll_async(policy, move || client.get_asset_report(&asset_report_token))
+    }
+
+    fn refresh_transactions_and_wait(
+        &self,
+        access_token: &str,
+        policy: &PollPolicy,
+    ) -> ResponseFuture<RefreshTransactionsResponse> {
+        let client = self.clone();
+        let policy = policy.clone();
+        let access_token = access_token.to_string();
+
+        Box::new(
+            self.get_item(&access_token)
+                .and_then(move |resp| {
+                    let before = last_transactions_update(&resp);
+                    client
+                        .refresh_transactions(&access_token)
+                        .map(move |refreshed| (client, access_token, before, refreshed))
+                })
+                .and_then(move |(client, access_token, before, refreshed)| {
+                    let started_at = Instant::now();
+                    future::loop_fn(refreshed, move |refreshed| {
+                        let policy = policy.clone();
+                        client.get_item(&access_token).and_then(
+                            move |resp| -> Box<dyn Future<Item = _, Error = Error> + Send> {
+                                if last_transactions_update(&resp) != before {
+                                    return Box::new(future::ok(future::Loop::Break(refreshed)));
+                                }
+                                match policy.next_check(started_at) {
+                                    Ok(wait) => Box::new(
+                                        tokio_timer::Delay::new(Instant::now() + wait)
+                                            .then(move |_| Ok(future::Loop::Continue(refreshed))),
+                                    ),
+                                    Err(err) => Box::new(future::err(err)),
+                                }
+                            },
+                        )
+                    })
+                }),
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::mock::MockPlaid;
+    use crate::transport::InMemoryTransport;
+
+    const PRODUCT_NOT_READY: &str = r#"{
+        "display_message": null,
+        "error_code": "PRODUCT_NOT_READY",
+        "error_message": "the requested product is not yet ready",
+        "error_type": "ITEM_ERROR",
+        "request_id": "Bxj2vM3E5gaj8iB"
+    }"#;
+
+    const TRANSACTIONS: &str = r#"{
+        "accounts": [],
+        "transactions": [],
+        "item": {
+            "available_products": [],
+            "billed_products": ["transactions"],
+            "error": null,
+            "institution_id": "ins_109508",
+            "item_id": "Ed6bjNrDLJfGvZWwnkQlfxwoNz54B5C97ejBr",
+            "webhook": ""
+        },
+        "total_transactions": 0,
+        "request_id": "45QSn"
+    }"#;
+
+    const ACCESS_TOKEN: &str = "access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6";
+
+    fn client(transport: &InMemoryTransport) -> Client {
+        Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+    }
+
+    #[test]
+    fn test_wait_for_transactions() {
+        let transport = InMemoryTransport::new()
+            .with_status_response("/transactions/get", 400, PRODUCT_NOT_READY)
+            .with_status_response("/transactions/get", 400, PRODUCT_NOT_READY)
+            .with_response("/transactions/get", TRANSACTIONS);
+        let policy = PollPolicy::new().with_interval(Duration::from_millis(1));
+
+        let resp = client(&transport)
+            .wait_for_transactions("access-sandbox-token", "2019-08-01", "2019-08-31", &policy)
+            .unwrap();
+
+        assert_eq!(0, resp.total_transactions);
+        assert_eq!(3, transport.requests_to("/transactions/get").len());
+    }
+
+    #[test]
+    fn test_wait_for_transactions_times_out() {
+        let transport = InMemoryTransport::new().with_status_response(
+            "/transactions/get",
+            400,
+            PRODUCT_NOT_READY,
+        );
+        let policy = PollPolicy::new()
+            .with_interval(Duration::from_millis(5))
+            .with_deadline(Duration::from_millis(20));
+
+        let err = match client(&transport).wait_for_transactions(
+            "access-sandbox-token",
+            "2019-08-01",
+            "2019-08-31",
+            &policy,
+        ) {
+            Err(err) => err,
+            Ok(_) => panic!("transactions were never ready"),
+        };
+
+        assert!(err.is_not_ready());
+        assert!(!err.is_timeout());
+        assert_eq!(Some(&ErrorCode::ProductNotReady), err.error_code());
+        assert!(transport.requests_to("/transactions/get").len() > 1);
+    }
+
+    #[test]
+    fn test_refresh_transactions_and_wait() {
+        let mock = MockPlaid::new();
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(mock.clone());
+        let policy = PollPolicy::new().with_interval(Duration::from_millis(1));
+
+        test_client
+            .refresh_transactions_and_wait(ACCESS_TOKEN, &policy)
+            .unwrap();
+
+        let status = test_client.get_item(ACCESS_TOKEN).unwrap().status.unwrap();
+        assert!(status
+            .transactions
+            .unwrap()
+            .last_successful_update
+            .is_some());
+    }
+
+    #[test]
+    fn test_refresh_transactions_and_wait_times_out() {
+        let item = r#"{
+            "item": {
+                "available_products": [],
+                "billed_products": ["transactions"],
+                "error": null,
+                "institution_id": "ins_109508",
+                "item_id": "Ed6bjNrDLJfGvZWwnkQlfxwoNz54B5C97ejBr",
+                "webhook": ""
+            },
+            "status": {
+                "transactions": {
+                    "last_successful_update": "2019-08-22T20:34:01Z",
+                    "last_failed_update": null
+                }
+            },
+            "request_id": "m8MDnv9okwxFNBV"
+        }"#;
+        let transport = InMemoryTransport::new()
+            .with_response("/item/get", item)
+            .with_response(
+                "/transactions/refresh",
+                r#"{"request_id": "1vwmF5TBQwiqfwP"}"#,
+            );
+        let policy = PollPolicy::new()
+            .with_interval(Duration::from_millis(5))
+            .with_deadline(Duration::from_millis(20));
+
+        let err = match client(&transport).refresh_transactions_and_wait(ACCESS_TOKEN, &policy) {
+            Err(err) => err,
+            Ok(_) => panic!("the update was never reported"),
+        };
+
+        assert!(err.is_not_ready());
+        assert!(!err.is_timeout());
+        assert!(err.api_error().is_none());
+        assert!(err.to_string().starts_with("product not updated after"));
+        assert_eq!(1, transport.requests_to("/transactions/refresh").len());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_async_refresh_transactions_and_wait() {
+        let test_client =
+            AsyncClient::new("client_id", "secret", "public_key", Environment::SANDBOX)
+                .with_transport(MockPlaid::new());
+        let policy = PollPolicy::new().with_interval(Duration::from_millis(1));
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        runtime
+            .block_on(test_client.refresh_transactions_and_wait(ACCESS_TOKEN, &policy))
+            .unwrap();
+        let resp = runtime
+            .block_on(test_client.get_item(ACCESS_TOKEN))
+            .unwrap();
+
+        let status = resp.status.unwrap().transactions.unwrap();
+        assert!(status.last_successful_update.is_some());
+    }
+}
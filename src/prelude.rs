@@ -0,0 +1,73 @@
+//! The prelude re-exports every product trait and type, along with the
+//! clients and their configuration, so a single glob import is enough to make
+//! calls:
+//!
+//! `use plaid_rust::prelude::*;`
+
+pub use crate::accounts::*;
+pub use crate::assets::*;
+pub use crate::auth::*;
+pub use crate::categories::*;
+pub use crate::holdings::*;
+pub use crate::identities::*;
+pub use crate::incomes::*;
+pub use crate::institutions::*;
+pub use crate::investment_transactions::*;
+pub use crate::items::*;
+pub use crate::liabilities::*;
+pub use crate::processors::*;
+pub use crate::sandbox::*;
+pub use crate::transactions::*;
+
+pub use crate::cursors::{CursorStore, InMemoryCursorStore};
+pub use crate::endpoint::{Credentials, Endpoint};
+pub use crate::environments::Environment;
+pub use crate::errors::{ApiError, Error, ErrorCode, ErrorType};
+#[cfg(feature = "async")]
+pub use crate::plaid::{AsyncClient, ResponseFuture};
+pub use crate::plaid::{Client, ClientBuilder};
+#[cfg(feature = "async")]
+pub use crate::polling::AsyncPolling;
+pub use crate::polling::{PollPolicy, Polling};
+pub use crate::rate_limit::{RateLimit, RateLimiter};
+pub use crate::retry::RetryPolicy;
+pub use crate::timeouts::Timeouts;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::mock::MockPlaid;
+
+    #[test]
+    fn test_namespaces() {
+        let client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(MockPlaid::new());
+
+        let public_token = client
+            .sandbox()
+            .create_public_token("ins_109508", &["transactions"])
+            .unwrap()
+            .public_token;
+        let access_token = client
+            .items()
+            .exchange_public_token(&public_token)
+            .unwrap()
+            .access_token;
+
+        let transactions = client
+            .transactions()
+            .get(&access_token, "2019-08-01", "2019-08-31")
+            .unwrap();
+        assert_eq!(12, transactions.total_transactions);
+        assert_eq!(
+            vec!["transactions"],
+            client
+                .items()
+                .get(&access_token)
+                .unwrap()
+                .item
+                .billed_products
+        );
+        assert!(client.items().remove(&access_token).unwrap().removed);
+    }
+}
@@ -1,8 +1,11 @@
+use crate::endpoint::Endpoint;
 use crate::errors::{Error, Kind};
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
 use serde::*;
 
-trait Processors {
+pub trait Processors {
     fn create_apex_token(
         &self,
         access_token: &str,
@@ -22,12 +25,15 @@ trait Processors {
 
 #[derive(Serialize)]
 struct CreateApexTokenRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     account_id: &'a str,
 }
 
+impl Endpoint for CreateApexTokenRequest<'_> {
+    type Response = CreateApexTokenResponse;
+    const PATH: &'static str = "/processor/apex/processor_token/create";
+}
+
 #[derive(Deserialize)]
 pub struct CreateApexTokenResponse {
     pub request_id: String,
@@ -36,12 +42,15 @@ pub struct CreateApexTokenResponse {
 
 #[derive(Serialize)]
 struct CreateDwollaTokenRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     account_id: &'a str,
 }
 
+impl Endpoint for CreateDwollaTokenRequest<'_> {
+    type Response = CreateDwollaTokenResponse;
+    const PATH: &'static str = "/processor/dwolla/processor_token/create";
+}
+
 #[derive(Deserialize)]
 pub struct CreateDwollaTokenResponse {
     pub request_id: String,
@@ -50,40 +59,39 @@ pub struct CreateDwollaTokenResponse {
 
 #[derive(Serialize)]
 struct CreateStripeTokenRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     account_id: &'a str,
 }
 
+impl Endpoint for CreateStripeTokenRequest<'_> {
+    type Response = CreateStripeTokenResponse;
+    const PATH: &'static str = "/processor/stripe/bank_account_token/create";
+}
+
 #[derive(Deserialize)]
 pub struct CreateStripeTokenResponse {
     pub request_id: String,
     pub stripe_bank_account_token: String,
 }
 
-impl<'a> Processors for Client<'a> {
+impl Processors for Client {
     fn create_apex_token(
         &self,
         access_token: &str,
         account_id: &str,
     ) -> Result<CreateApexTokenResponse, Error> {
-        if access_token == "" || account_id == "" {
+        if access_token.is_empty() || account_id.is_empty() {
             Err(Error::new(Kind::ValidationError(
                 "access token and account ID must be specified",
             )))?
         }
 
         let req = CreateApexTokenRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             account_id,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/processor/apex/processor_token/create", &json_body))
+        self.send(&req)
     }
 
     fn create_dwolla_token(
@@ -91,22 +99,18 @@ impl<'a> Processors for Client<'a> {
         access_token: &str,
         account_id: &str,
     ) -> Result<CreateDwollaTokenResponse, Error> {
-        if access_token == "" || account_id == "" {
+        if access_token.is_empty() || account_id.is_empty() {
             Err(Error::new(Kind::ValidationError(
                 "access token and account ID must be specified",
             )))?
         }
 
         let req = CreateDwollaTokenRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             account_id,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/processor/dwolla/processor_token/create", &json_body))
+        self.send(&req)
     }
 
     fn create_stripe_token(
@@ -114,23 +118,155 @@ impl<'a> Processors for Client<'a> {
         access_token: &str,
         account_id: &str,
     ) -> Result<CreateStripeTokenResponse, Error> {
-        if access_token == "" || account_id == "" {
+        if access_token.is_empty() || account_id.is_empty() {
             Err(Error::new(Kind::ValidationError(
                 "access token and account ID must be specified",
             )))?
         }
 
         let req = CreateStripeTokenRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             account_id,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| {
-                self.call("/processor/stripe/bank_account_token/create", &json_body)
-            })
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncProcessors {
+    fn create_apex_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> ResponseFuture<CreateApexTokenResponse>;
+    fn create_dwolla_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> ResponseFuture<CreateDwollaTokenResponse>;
+    fn create_stripe_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> ResponseFuture<CreateStripeTokenResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncProcessors for AsyncClient {
+    fn create_apex_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> ResponseFuture<CreateApexTokenResponse> {
+        if access_token.is_empty() || account_id.is_empty() {
+            return fail(Kind::ValidationError(
+                "access token and account ID must be specified",
+            ));
+        }
+
+        let req = CreateApexTokenRequest {
+            access_token,
+            account_id,
+        };
+
+        self.send(&req)
+    }
+
+    fn create_dwolla_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> ResponseFuture<CreateDwollaTokenResponse> {
+        if access_token.is_empty() || account_id.is_empty() {
+            return fail(Kind::ValidationError(
+                "access token and account ID must be specified",
+            ));
+        }
+
+        let req = CreateDwollaTokenRequest {
+            access_token,
+            account_id,
+        };
+
+        self.send(&req)
+    }
+
+    fn create_stripe_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> ResponseFuture<CreateStripeTokenResponse> {
+        if access_token.is_empty() || account_id.is_empty() {
+            return fail(Kind::ValidationError(
+                "access token and account ID must be specified",
+            ));
+        }
+
+        let req = CreateStripeTokenRequest {
+            access_token,
+            account_id,
+        };
+
+        self.send(&req)
+    }
+}
+
+/// ProcessorsApi groups the processor token endpoints, e.g.
+/// `client.processors().create_apex_token(..)`.
+pub struct ProcessorsApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl ProcessorsApi<'_, Client> {
+    pub fn create_apex_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> Result<CreateApexTokenResponse, Error> {
+        self.client.create_apex_token(access_token, account_id)
+    }
+
+    pub fn create_dwolla_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> Result<CreateDwollaTokenResponse, Error> {
+        self.client.create_dwolla_token(access_token, account_id)
+    }
+
+    pub fn create_stripe_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> Result<CreateStripeTokenResponse, Error> {
+        self.client.create_stripe_token(access_token, account_id)
+    }
+}
+
+#[cfg(feature = "async")]
+impl ProcessorsApi<'_, AsyncClient> {
+    pub fn create_apex_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> ResponseFuture<CreateApexTokenResponse> {
+        self.client.create_apex_token(access_token, account_id)
+    }
+
+    pub fn create_dwolla_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> ResponseFuture<CreateDwollaTokenResponse> {
+        self.client.create_dwolla_token(access_token, account_id)
+    }
+
+    pub fn create_stripe_token(
+        &self,
+        access_token: &str,
+        account_id: &str,
+    ) -> ResponseFuture<CreateStripeTokenResponse> {
+        self.client.create_stripe_token(access_token, account_id)
     }
 }
@@ -0,0 +1,415 @@
+use crate::transport::normalize_endpoint;
+use std::collections::HashMap;
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+/// RateLimit is a token bucket quota: up to `burst` requests may be made at
+/// once, and the bucket refills at `requests` per `period`. Its fields are
+/// only set through the constructors, which reject quotas that never refill.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct RateLimit {
+    requests: u32,
+    period: Duration,
+    burst: u32,
+}
+
+impl RateLimit {
+    /// Panics if `requests` or `period` is zero, since such a bucket would
+    /// never refill.
+    pub fn new(requests: u32, period: Duration) -> RateLimit {
+        assert!(
+            requests > 0,
+            "RateLimit requires at least one request per period"
+        );
+        assert!(
+            period > Duration::from_secs(0),
+            "RateLimit requires a non-zero period"
+        );
+
+        RateLimit {
+            requests,
+            period,
+            burst: requests,
+        }
+    }
+
+    pub fn per_second(requests: u32) -> RateLimit {
+        RateLimit::new(requests, Duration::from_secs(1))
+    }
+
+    pub fn per_minute(requests: u32) -> RateLimit {
+        RateLimit::new(requests, Duration::from_secs(60))
+    }
+
+    /// Caps how many requests may be made back to back before throttling.
+    /// Panics if `burst` is zero.
+    pub fn with_burst(mut self, burst: u32) -> RateLimit {
+        assert!(
+            burst > 0,
+            "RateLimit requires a burst of at least one request"
+        );
+        self.burst = burst;
+        self
+    }
+
+    pub fn requests(&self) -> u32 {
+        self.requests
+    }
+
+    pub fn period(&self) -> Duration {
+        self.period
+    }
+
+    pub fn burst(&self) -> u32 {
+        self.burst
+    }
+
+    fn refill_rate(&self) -> f64 {
+        f64::from(self.requests) / self.period.as_secs_f64()
+    }
+}
+
+/// RateLimiter throttles calls on the client side so bulk jobs stay within
+/// Plaid's per-endpoint and per-item limits instead of running into
+/// `RATE_LIMIT_EXCEEDED`. Limits are configured per endpoint path, with an
+/// optional fallback for endpoints that have no explicit limit. Item limits are
+/// tracked separately for every access token.
+///
+/// Clones share their buckets, so one limiter can throttle several clients.
+#[derive(Clone, Default)]
+pub struct RateLimiter {
+    inner: Arc<Mutex<LimiterState>>,
+}
+
+#[derive(Default)]
+struct LimiterState {
+    default_limits: Limits,
+    endpoint_limits: HashMap<String, Limits>,
+    buckets: HashMap<BucketKey, TokenBucket>,
+    sweep_at: usize,
+}
+
+#[derive(Clone, Copy, Default)]
+struct Limits {
+    endpoint: Option<RateLimit>,
+    item: Option<RateLimit>,
+}
+
+#[derive(Clone, PartialEq, Eq, Hash)]
+struct BucketKey {
+    endpoint: String,
+    access_token: Option<String>,
+}
+
+impl RateLimiter {
+    pub fn new() -> RateLimiter {
+        RateLimiter::default()
+    }
+
+    /// Limits calls to any endpoint without an endpoint limit of its own.
+    pub fn with_default_limit(self, limit: RateLimit) -> RateLimiter {
+        self.inner.lock().unwrap().default_limits.endpoint = Some(limit);
+        self
+    }
+
+    /// Limits calls per access token to any endpoint without an item limit of
+    /// its own.
+    pub fn with_default_item_limit(self, limit: RateLimit) -> RateLimiter {
+        self.inner.lock().unwrap().default_limits.item = Some(limit);
+        self
+    }
+
+    /// Limits the total rate of calls to `endpoint`.
+    pub fn with_endpoint_limit(self, endpoint: &str, limit: RateLimit) -> RateLimiter {
+        self.limits_for(endpoint, |limits| limits.endpoint = Some(limit));
+        self
+    }
+
+    /// Limits the rate of calls to `endpoint` for each access token.
+    pub fn with_item_limit(self, endpoint: &str, limit: RateLimit) -> RateLimiter {
+        self.limits_for(endpoint, |limits| limits.item = Some(limit));
+        self
+    }
+
+    fn limits_for<F: FnOnce(&mut Limits)>(&self, endpoint: &str, update: F) {
+        let mut state = self.inner.lock().unwrap();
+        update(
+            state
+                .endpoint_limits
+                .entry(normalize_endpoint(endpoint))
+                .or_default(),
+        );
+    }
+
+    /// Reserves a slot for a call to `endpoint` made with `access_token` and
+    /// returns how long the caller has to wait before making it.
+    pub fn acquire(&self, endpoint: &str, access_token: Option<&str>) -> Duration {
+        self.acquire_at(endpoint, access_token, Instant::now())
+    }
+
+    pub(crate) fn acquire_at(
+        &self,
+        endpoint: &str,
+        access_token: Option<&str>,
+        now: Instant,
+    ) -> Duration {
+        let mut state = self.inner.lock().unwrap();
+        let endpoint = normalize_endpoint(endpoint);
+
+        let configured = state.endpoint_limits.get(&endpoint).cloned();
+        let limits = Limits {
+            endpoint: configured
+                .and_then(|limits| limits.endpoint)
+                .or(state.default_limits.endpoint),
+            item: configured
+                .and_then(|limits| limits.item)
+                .or(state.default_limits.item),
+        };
+
+        let mut wait = Duration::from_secs(0);
+        if let Some(limit) = limits.endpoint {
+            let key = BucketKey {
+                endpoint: endpoint.clone(),
+                access_token: None,
+            };
+            wait = wait.max(state.reserve(key, limit, now));
+        }
+        if let (Some(limit), Some(access_token)) = (limits.item, access_token) {
+            let key = BucketKey {
+                endpoint,
+                access_token: Some(access_token.to_string()),
+            };
+            wait = wait.max(state.reserve(key, limit, now));
+        }
+
+        wait
+    }
+}
+
+// Buckets are swept once the map has doubled in size since the last sweep, so
+// the cost stays amortized over the reservations that grew it.
+const MIN_SWEEP_AT: usize = 64;
+
+impl LimiterState {
+    fn reserve(&mut self, key: BucketKey, limit: RateLimit, now: Instant) -> Duration {
+        if self.buckets.len() >= self.sweep_at.max(MIN_SWEEP_AT) {
+            self.sweep(now);
+        }
+
+        self.buckets
+            .entry(key)
+            .or_insert_with(|| TokenBucket::new(limit, now))
+            .reserve(limit, now)
+    }
+
+    // sweep drops buckets that have refilled completely. A full bucket behaves
+    // exactly like a new one, so this only forgets items that have gone idle.
+    fn sweep(&mut self, now: Instant) {
+        self.buckets.retain(|_, bucket| !bucket.is_full(now));
+        self.sweep_at = self.buckets.len() * 2;
+    }
+}
+
+// TokenBucket lets its token count go negative, so that concurrent callers
+// queue up behind each other instead of all waking at the same instant.
+struct TokenBucket {
+    tokens: f64,
+    updated_at: Instant,
+    limit: RateLimit,
+}
+
+impl TokenBucket {
+    fn new(limit: RateLimit, now: Instant) -> TokenBucket {
+        TokenBucket {
+            tokens: f64::from(limit.burst),
+            updated_at: now,
+            limit,
+        }
+    }
+
+    fn reserve(&mut self, limit: RateLimit, now: Instant) -> Duration {
+        let elapsed = now
+            .checked_duration_since(self.updated_at)
+            .unwrap_or_default();
+        self.tokens =
+            (self.tokens + elapsed.as_secs_f64() * limit.refill_rate()).min(f64::from(limit.burst));
+        self.updated_at = now;
+        self.limit = limit;
+
+        self.tokens -= 1.0;
+        if self.tokens >= 0.0 {
+            Duration::from_secs(0)
+        } else {
+            Duration::from_secs_f64(-self.tokens / limit.refill_rate())
+        }
+    }
+
+    fn is_full(&self, now: Instant) -> bool {
+        let elapsed = now
+            .checked_duration_since(self.updated_at)
+            .unwrap_or_default();
+        self.tokens + elapsed.as_secs_f64() * self.limit.refill_rate()
+            >= f64::from(self.limit.burst)
+    }
+}
+
+// access_token_of pulls the access token out of a serialized request body so
+// that item limits can be applied without every endpoint passing it along.
+pub(crate) fn access_token_of(body: &str) -> Option<String> {
+    serde_json::from_str::<serde_json::Value>(body)
+        .ok()
+        .and_then(|value| {
+            value
+                .get("access_token")
+                .and_then(|token| token.as_str())
+                .map(String::from)
+        })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::items::Items;
+    use crate::plaid::Client;
+    use crate::transport::InMemoryTransport;
+
+    #[test]
+    fn test_endpoint_limit() {
+        let limiter =
+            RateLimiter::new().with_endpoint_limit("/transactions/get", RateLimit::per_second(2));
+        let now = Instant::now();
+
+        assert_eq!(
+            Duration::from_secs(0),
+            limiter.acquire_at("/transactions/get", Some("a"), now)
+        );
+        assert_eq!(
+            Duration::from_secs(0),
+            limiter.acquire_at("/transactions/get", Some("b"), now)
+        );
+        assert_eq!(
+            Duration::from_millis(500),
+            limiter.acquire_at("/transactions/get", Some("c"), now)
+        );
+        assert_eq!(
+            Duration::from_millis(1000),
+            limiter.acquire_at("/transactions/get", Some("d"), now)
+        );
+        assert_eq!(
+            Duration::from_secs(0),
+            limiter.acquire_at("/accounts/get", Some("a"), now)
+        );
+        assert_eq!(
+            Duration::from_millis(500),
+            limiter.acquire_at("/transactions/get", Some("e"), now + Duration::from_secs(1))
+        );
+    }
+
+    #[test]
+    fn test_item_limit() {
+        let limiter =
+            RateLimiter::new().with_item_limit("/accounts/balance/get", RateLimit::per_minute(1));
+        let now = Instant::now();
+
+        assert_eq!(
+            Duration::from_secs(0),
+            limiter.acquire_at("/accounts/balance/get", Some("a"), now)
+        );
+        assert_eq!(
+            Duration::from_secs(0),
+            limiter.acquire_at("/accounts/balance/get", Some("b"), now)
+        );
+        assert_eq!(
+            Duration::from_secs(60),
+            limiter.acquire_at("/accounts/balance/get", Some("a"), now)
+        );
+        assert_eq!(
+            Duration::from_secs(0),
+            limiter.acquire_at("/accounts/balance/get", None, now)
+        );
+    }
+
+    #[test]
+    fn test_default_limit() {
+        let limiter = RateLimiter::new()
+            .with_default_limit(RateLimit::per_second(1))
+            .with_endpoint_limit("/categories/get", RateLimit::per_second(10));
+        let now = Instant::now();
+
+        limiter.acquire_at("/item/get", None, now);
+        assert_eq!(
+            Duration::from_secs(1),
+            limiter.acquire_at("item/get", None, now)
+        );
+        limiter.acquire_at("/categories/get", None, now);
+        assert_eq!(
+            Duration::from_secs(0),
+            limiter.acquire_at("/categories/get", None, now)
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "at least one request")]
+    fn test_zero_requests() {
+        RateLimit::per_second(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "non-zero period")]
+    fn test_zero_period() {
+        RateLimit::new(1, Duration::from_secs(0));
+    }
+
+    #[test]
+    fn test_accessors() {
+        let limit = RateLimit::per_minute(30).with_burst(5);
+
+        assert_eq!(30, limit.requests());
+        assert_eq!(Duration::from_secs(60), limit.period());
+        assert_eq!(5, limit.burst());
+    }
+
+    #[test]
+    fn test_idle_buckets_are_dropped() {
+        let limiter =
+            RateLimiter::new().with_item_limit("/accounts/balance/get", RateLimit::per_second(1));
+        let now = Instant::now();
+
+        for i in 0..MIN_SWEEP_AT {
+            let token = format!("access-sandbox-{}", i);
+            limiter.acquire_at("/accounts/balance/get", Some(&token), now);
+        }
+        assert_eq!(MIN_SWEEP_AT, limiter.inner.lock().unwrap().buckets.len());
+
+        let later = now + Duration::from_secs(1);
+        limiter.acquire_at("/accounts/balance/get", Some("access-sandbox-a"), later);
+        assert_eq!(1, limiter.inner.lock().unwrap().buckets.len());
+        assert_eq!(
+            Duration::from_secs(1),
+            limiter.acquire_at("/accounts/balance/get", Some("access-sandbox-a"), later)
+        );
+    }
+
+    #[test]
+    fn test_client_reserves_per_item() {
+        let transport = InMemoryTransport::new().with_response(
+            "/item/remove",
+            r#"{"request_id": "m8MDnv9okwxFNBV", "removed": true}"#,
+        );
+        let limiter = RateLimiter::new().with_item_limit("/item/remove", RateLimit::per_minute(1));
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport)
+            .with_rate_limiter(limiter.clone());
+
+        test_client.remove_item("access-sandbox-a").unwrap();
+        test_client.remove_item("access-sandbox-b").unwrap();
+
+        assert!(
+            limiter.acquire("/item/remove", Some("access-sandbox-a")) > Duration::from_secs(50)
+        );
+        assert!(
+            limiter.acquire("/item/remove", Some("access-sandbox-c")) == Duration::from_secs(0)
+        );
+    }
+}
@@ -0,0 +1,288 @@
+use crate::errors::{Error, ErrorCode, ErrorType};
+#[cfg(feature = "async")]
+use futures::{future, Future};
+use rand::Rng;
+use std::thread;
+use std::time::Duration;
+#[cfg(feature = "async")]
+use std::time::Instant;
+
+/// RetryPolicy decides whether a failed call is attempted again and how long
+/// the client waits in between. Backoff grows exponentially from
+/// `initial_backoff` by `multiplier` up to `max_backoff`; with `jitter` on, the
+/// actual wait is drawn uniformly between zero and that value.
+///
+/// The default policy makes up to three attempts and retries Plaid's
+/// transient failures: `INTERNAL_SERVER_ERROR`, `PLANNED_MAINTENANCE`, every
+/// `RATE_LIMIT_EXCEEDED` error, 429 and 5xx statuses without a Plaid error
+/// body, network errors and timeouts.
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub initial_backoff: Duration,
+    pub max_backoff: Duration,
+    pub multiplier: f64,
+    pub jitter: bool,
+    pub retryable_error_types: Vec<ErrorType>,
+    pub retryable_error_codes: Vec<ErrorCode>,
+    pub retryable_statuses: Vec<u16>,
+    pub retry_network_errors: bool,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 3,
+            initial_backoff: Duration::from_millis(500),
+            max_backoff: Duration::from_secs(30),
+            multiplier: 2.0,
+            jitter: true,
+            retryable_error_types: vec![ErrorType::RateLimitExceeded],
+            retryable_error_codes: vec![
+                ErrorCode::InternalServerError,
+                ErrorCode::PlannedMaintenance,
+            ],
+            retryable_statuses: vec![429, 500, 502, 503, 504],
+            retry_network_errors: true,
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// A policy that makes a single attempt. This is what `Client::new` uses.
+    pub fn never() -> RetryPolicy {
+        RetryPolicy {
+            max_attempts: 1,
+            ..RetryPolicy::default()
+        }
+    }
+
+    pub fn with_max_attempts(mut self, max_attempts: u32) -> RetryPolicy {
+        self.max_attempts = max_attempts;
+        self
+    }
+
+    pub fn with_backoff(mut self, initial_backoff: Duration, max_backoff: Duration) -> RetryPolicy {
+        self.initial_backoff = initial_backoff;
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn with_jitter(mut self, jitter: bool) -> RetryPolicy {
+        self.jitter = jitter;
+        self
+    }
+
+    /// Returns true if `err` is worth another attempt under this policy.
+    pub fn is_retryable(&self, err: &Error) -> bool {
+        if let Some(api_error) = err.api_error() {
+            return self.retryable_error_types.contains(&api_error.error_type)
+                || self.retryable_error_codes.contains(&api_error.error_code);
+        }
+
+        match err.status() {
+            Some(status) => self.retryable_statuses.contains(&status),
+            None => self.retry_network_errors && (err.is_network() || err.is_timeout()),
+        }
+    }
+
+    /// Returns true if this policy already retries `RATE_LIMIT_EXCEEDED`
+    /// errors.
+    pub fn retries_rate_limits(&self) -> bool {
+        self.max_attempts > 1
+            && self
+                .retryable_error_types
+                .contains(&ErrorType::RateLimitExceeded)
+    }
+
+    /// Returns how long to wait after the given (1-based) failed attempt.
+    pub fn backoff(&self, attempt: u32) -> Duration {
+        let exponent = attempt.saturating_sub(1) as i32;
+        let max = self.max_backoff.as_secs_f64();
+        let backoff =
+            (self.initial_backoff.as_secs_f64() * self.multiplier.powi(exponent)).min(max);
+
+        if self.jitter && backoff > 0.0 {
+            Duration::from_secs_f64(rand::thread_rng().gen_range(0.0, backoff))
+        } else {
+            Duration::from_secs_f64(backoff)
+        }
+    }
+}
+
+// retry runs `call` until it succeeds, fails with an error the policy does not
+// retry, or runs out of attempts. The returned error records how many attempts
+// were made.
+pub(crate) fn retry<T, F>(policy: &RetryPolicy, mut call: F) -> Result<T, Error>
+where
+    F: FnMut() -> Result<T, Error>,
+{
+    let mut attempt = 1;
+    loop {
+        match call() {
+            Err(ref err) if attempt < policy.max_attempts && policy.is_retryable(err) => {
+                thread::sleep(policy.backoff(attempt));
+                attempt += 1;
+            }
+            result => return result.map_err(|err| err.with_attempts(attempt)),
+        }
+    }
+}
+
+// retry_async is the non-blocking counterpart of `retry`, waiting out the
+// backoff on the tokio timer instead of blocking the thread.
+#[cfg(feature = "async")]
+pub(crate) fn retry_async<T, F, R>(
+    policy: RetryPolicy,
+    call: F,
+) -> impl Future<Item = T, Error = Error> + Send
+where
+    T: Send + 'static,
+    F: Fn() -> R + Send + 'static,
+    R: Future<Item = T, Error = Error> + Send + 'static,
+{
+    future::loop_fn(1, move |attempt| {
+        let policy = policy.clone();
+        call().then(
+            move |result| -> Box<dyn Future<Item = _, Error = Error> + Send> {
+                match result {
+                    Err(ref err) if attempt < policy.max_attempts && policy.is_retryable(err) => {
+                        let deadline = Instant::now() + policy.backoff(attempt);
+                        Box::new(
+                            tokio_timer::Delay::new(deadline)
+                                .then(move |_| Ok(future::Loop::Continue(attempt + 1))),
+                        )
+                    }
+                    Ok(value) => Box::new(future::ok(future::Loop::Break(value))),
+                    Err(err) => Box::new(future::err(err.with_attempts(attempt))),
+                }
+            },
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::environments::Environment;
+    use crate::items::Items;
+    use crate::plaid::Client;
+    use crate::transport::InMemoryTransport;
+
+    const INTERNAL_SERVER_ERROR: &str = r#"{
+        "display_message": null,
+        "error_code": "INTERNAL_SERVER_ERROR",
+        "error_message": "an unexpected error occurred",
+        "error_type": "API_ERROR",
+        "request_id": "HNTDNrA8F1shFEW"
+    }"#;
+
+    const ITEM_LOGIN_REQUIRED: &str = r#"{
+        "display_message": null,
+        "error_code": "ITEM_LOGIN_REQUIRED",
+        "error_message": "the login details of this item have changed",
+        "error_type": "ITEM_ERROR",
+        "request_id": "HNTDNrA8F1shFEW"
+    }"#;
+
+    const REMOVE_ITEM: &str = r#"{"request_id": "m8MDnv9okwxFNBV", "removed": true}"#;
+
+    fn test_policy() -> RetryPolicy {
+        RetryPolicy::default().with_backoff(Duration::from_millis(0), Duration::from_millis(0))
+    }
+
+    #[test]
+    fn test_retries_until_success() {
+        let transport = InMemoryTransport::new()
+            .with_status_response("/item/remove", 500, INTERNAL_SERVER_ERROR)
+            .with_status_response("/item/remove", 500, INTERNAL_SERVER_ERROR)
+            .with_response("/item/remove", REMOVE_ITEM);
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+            .with_retry_policy(test_policy());
+
+        let resp = test_client.remove_item("access-sandbox-token").unwrap();
+
+        assert!(resp.removed);
+        assert_eq!(3, transport.requests_to("/item/remove").len());
+    }
+
+    #[test]
+    fn test_reports_attempts_when_exhausted() {
+        let transport = InMemoryTransport::new().with_status_response(
+            "/item/remove",
+            500,
+            INTERNAL_SERVER_ERROR,
+        );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+            .with_retry_policy(test_policy().with_max_attempts(4));
+
+        let err = test_client
+            .remove_item("access-sandbox-token")
+            .err()
+            .unwrap();
+
+        assert_eq!(Some(&ErrorCode::InternalServerError), err.error_code());
+        assert_eq!(4, err.attempts());
+        assert_eq!(4, transport.requests_to("/item/remove").len());
+    }
+
+    #[test]
+    fn test_does_not_retry_item_errors() {
+        let transport = InMemoryTransport::new()
+            .with_status_response("/item/remove", 400, ITEM_LOGIN_REQUIRED)
+            .with_response("/item/remove", REMOVE_ITEM);
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+            .with_retry_policy(test_policy());
+
+        let err = test_client
+            .remove_item("access-sandbox-token")
+            .err()
+            .unwrap();
+
+        assert_eq!(Some(&ErrorCode::ItemLoginRequired), err.error_code());
+        assert_eq!(1, err.attempts());
+        assert_eq!(1, transport.requests_to("/item/remove").len());
+    }
+
+    #[test]
+    fn test_backoff() {
+        let policy = RetryPolicy::default()
+            .with_backoff(Duration::from_millis(100), Duration::from_millis(350))
+            .with_jitter(false);
+
+        assert_eq!(Duration::from_millis(100), policy.backoff(1));
+        assert_eq!(Duration::from_millis(200), policy.backoff(2));
+        assert_eq!(Duration::from_millis(350), policy.backoff(3));
+
+        let jittered = policy.with_jitter(true);
+        for attempt in 1..5 {
+            assert!(jittered.backoff(attempt) <= Duration::from_millis(350));
+        }
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_retries_async() {
+        use crate::items::AsyncItems;
+        use crate::plaid::AsyncClient;
+
+        let transport = InMemoryTransport::new()
+            .with_status_response("/item/remove", 500, INTERNAL_SERVER_ERROR)
+            .with_response("/item/remove", REMOVE_ITEM);
+        let test_client =
+            AsyncClient::new("client_id", "secret", "public_key", Environment::SANDBOX)
+                .with_transport(transport.clone())
+                .with_retry_policy(test_policy());
+
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let resp = runtime
+            .block_on(test_client.remove_item("access-sandbox-token"))
+            .unwrap();
+
+        assert!(resp.removed);
+        assert_eq!(2, transport.requests_to("/item/remove").len());
+    }
+}
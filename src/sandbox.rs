@@ -1,5 +1,8 @@
+use crate::endpoint::{Credentials, Endpoint};
 use crate::errors::{Error, Kind};
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
 use serde::*;
 
 pub trait Sandbox {
@@ -15,7 +18,12 @@ pub trait Sandbox {
 struct CreateSandboxPublicTokenRequest<'a> {
     institution_id: &'a str,
     initial_products: &'a [&'a str],
-    public_key: &'a str,
+}
+
+impl Endpoint for CreateSandboxPublicTokenRequest<'_> {
+    type Response = CreateSandboxPublicTokenResponse;
+    const PATH: &'static str = "/sandbox/public_token/create";
+    const CREDENTIALS: Credentials = Credentials::PublicKey;
 }
 
 #[derive(Deserialize)]
@@ -26,24 +34,27 @@ pub struct CreateSandboxPublicTokenResponse {
 
 #[derive(Serialize)]
 struct ResetSandboxItemRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
 }
 
+impl Endpoint for ResetSandboxItemRequest<'_> {
+    type Response = ResetSandboxItemResponse;
+    const PATH: &'static str = "/sandbox/item/reset_login";
+}
+
 #[derive(Deserialize)]
 pub struct ResetSandboxItemResponse {
     pub request_id: String,
     pub reset_login: bool,
 }
 
-impl<'a> Sandbox for Client<'a> {
+impl Sandbox for Client {
     fn create_sandbox_public_token(
         &self,
         institution_id: &str,
         initial_products: &[&str],
     ) -> Result<CreateSandboxPublicTokenResponse, Error> {
-        if institution_id == "" || initial_products.len() == 0 {
+        if institution_id.is_empty() || initial_products.is_empty() {
             return Err(Error::new(Kind::ValidationError(
                 "institution id and initial products must be specified",
             )));
@@ -52,27 +63,97 @@ impl<'a> Sandbox for Client<'a> {
         let req = CreateSandboxPublicTokenRequest {
             institution_id,
             initial_products,
-            public_key: self.public_key,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/sandbox/public_token/create", &json_body))
+        self.send(&req)
     }
 
     fn reset_sandbox_item(&self, access_token: &str) -> Result<ResetSandboxItemResponse, Error> {
-        if access_token == "" {
+        if access_token.is_empty() {
             return Err(Error::new(Kind::EmptyToken));
         }
 
-        let req = ResetSandboxItemRequest {
-            client_id: self.client_id,
-            secret: self.secret,
-            access_token,
+        let req = ResetSandboxItemRequest { access_token };
+
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncSandbox {
+    fn create_sandbox_public_token(
+        &self,
+        institution_id: &str,
+        initial_products: &[&str],
+    ) -> ResponseFuture<CreateSandboxPublicTokenResponse>;
+    fn reset_sandbox_item(&self, access_token: &str) -> ResponseFuture<ResetSandboxItemResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncSandbox for AsyncClient {
+    fn create_sandbox_public_token(
+        &self,
+        institution_id: &str,
+        initial_products: &[&str],
+    ) -> ResponseFuture<CreateSandboxPublicTokenResponse> {
+        if institution_id.is_empty() || initial_products.is_empty() {
+            return fail(Kind::ValidationError(
+                "institution id and initial products must be specified",
+            ));
+        }
+
+        let req = CreateSandboxPublicTokenRequest {
+            institution_id,
+            initial_products,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/sandbox/item/reset_login", &json_body))
+        self.send(&req)
+    }
+
+    fn reset_sandbox_item(&self, access_token: &str) -> ResponseFuture<ResetSandboxItemResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = ResetSandboxItemRequest { access_token };
+
+        self.send(&req)
+    }
+}
+
+/// SandboxApi groups the sandbox endpoints, e.g.
+/// `client.sandbox().create_public_token(..)`.
+pub struct SandboxApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl SandboxApi<'_, Client> {
+    pub fn create_public_token(
+        &self,
+        institution_id: &str,
+        initial_products: &[&str],
+    ) -> Result<CreateSandboxPublicTokenResponse, Error> {
+        self.client
+            .create_sandbox_public_token(institution_id, initial_products)
+    }
+
+    pub fn reset_item(&self, access_token: &str) -> Result<ResetSandboxItemResponse, Error> {
+        self.client.reset_sandbox_item(access_token)
+    }
+}
+
+#[cfg(feature = "async")]
+impl SandboxApi<'_, AsyncClient> {
+    pub fn create_public_token(
+        &self,
+        institution_id: &str,
+        initial_products: &[&str],
+    ) -> ResponseFuture<CreateSandboxPublicTokenResponse> {
+        self.client
+            .create_sandbox_public_token(institution_id, initial_products)
+    }
+
+    pub fn reset_item(&self, access_token: &str) -> ResponseFuture<ResetSandboxItemResponse> {
+        self.client.reset_sandbox_item(access_token)
     }
 }
@@ -0,0 +1,85 @@
+use crate::transport::normalize_endpoint;
+use std::collections::HashMap;
+use std::time::Duration;
+
+/// Timeouts bounds how long a single attempt at a call may take, per endpoint.
+/// Endpoints without a timeout of their own use the default of 30 seconds.
+///
+/// The defaults give endpoints that make Plaid wait on the institution, or
+/// return large bodies, more time: two minutes for `/asset_report/get` and
+/// `/asset_report/pdf/get`, and one minute for `/accounts/balance/get`,
+/// `/transactions/get` and `/investments/transactions/get`.
+#[derive(Clone, Debug)]
+pub struct Timeouts {
+    default: Duration,
+    endpoints: HashMap<String, Duration>,
+}
+
+impl Default for Timeouts {
+    fn default() -> Timeouts {
+        Timeouts::none()
+            .with_endpoint("/asset_report/get", Duration::from_secs(120))
+            .with_endpoint("/asset_report/pdf/get", Duration::from_secs(120))
+            .with_endpoint("/accounts/balance/get", Duration::from_secs(60))
+            .with_endpoint("/transactions/get", Duration::from_secs(60))
+            .with_endpoint("/investments/transactions/get", Duration::from_secs(60))
+    }
+}
+
+impl Timeouts {
+    pub fn new() -> Timeouts {
+        Timeouts::default()
+    }
+
+    /// Timeouts with no endpoint overrides, giving every endpoint the default.
+    pub fn none() -> Timeouts {
+        Timeouts {
+            default: Duration::from_secs(30),
+            endpoints: HashMap::new(),
+        }
+    }
+
+    /// Sets the timeout of endpoints without one of their own.
+    pub fn with_default(mut self, timeout: Duration) -> Timeouts {
+        self.default = timeout;
+        self
+    }
+
+    pub fn with_endpoint(mut self, endpoint: &str, timeout: Duration) -> Timeouts {
+        self.endpoints.insert(normalize_endpoint(endpoint), timeout);
+        self
+    }
+
+    /// Returns the timeout of calls to `endpoint`.
+    pub fn for_endpoint(&self, endpoint: &str) -> Duration {
+        self.endpoints
+            .get(&normalize_endpoint(endpoint))
+            .cloned()
+            .unwrap_or(self.default)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_for_endpoint() {
+        let timeouts = Timeouts::new()
+            .with_default(Duration::from_secs(10))
+            .with_endpoint("item/get", Duration::from_secs(5));
+
+        assert_eq!(Duration::from_secs(5), timeouts.for_endpoint("/item/get"));
+        assert_eq!(Duration::from_secs(10), timeouts.for_endpoint("/auth/get"));
+        assert_eq!(
+            Duration::from_secs(120),
+            timeouts.for_endpoint("/asset_report/get")
+        );
+        assert_eq!(
+            Duration::from_secs(10),
+            Timeouts::none()
+                .with_default(Duration::from_secs(10))
+                .for_endpoint("/asset_report/get")
+        );
+    }
+}
@@ -1,10 +1,21 @@
 use crate::accounts::Account;
-use crate::errors::{Error, Kind};
+use crate::cursors::CursorStore;
+use crate::endpoint::Endpoint;
+use crate::errors::{Error, ErrorCode, Kind};
 use crate::items::Item;
+use crate::pagination::{Pager, MAX_PAGE_SIZE};
 use crate::plaid::Client;
+#[cfg(feature = "async")]
+use crate::plaid::{fail, AsyncClient, ResponseFuture};
+use crate::retry::RetryPolicy;
+#[cfg(feature = "async")]
+use futures::{Async, Future, Poll, Stream};
 use serde::*;
+use std::sync::Arc;
+use std::thread;
+use std::time::Duration;
 
-trait Transactions {
+pub trait Transactions {
     fn get_transactions(
         &self,
         access_token: &str,
@@ -18,6 +29,16 @@ trait Transactions {
         end_date: &str,
         options: Option<GetTransactionsRequestOptions>,
     ) -> Result<GetTransactionsResponse, Error>;
+    fn refresh_transactions(
+        &self,
+        access_token: &str,
+    ) -> Result<RefreshTransactionsResponse, Error>;
+    fn sync_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+        options: Option<SyncTransactionsRequestOptions>,
+    ) -> Result<SyncTransactionsResponse, Error>;
 }
 
 #[derive(Deserialize)]
@@ -25,7 +46,7 @@ pub struct Transaction {
     pub account_id: String,
     pub amount: f64,
     pub iso_currency_code: String,
-    pub unofficial_currency_code: String,
+    pub unofficial_currency_code: Option<String>,
     pub category: Vec<String>,
     pub category_id: String,
     pub date: String,
@@ -33,38 +54,38 @@ pub struct Transaction {
     pub name: String,
     pub payment_meta: PaymentMeta,
     pub pending: bool,
-    pub pending_transaction_id: String,
-    pub account_owner: String,
+    pub pending_transaction_id: Option<String>,
+    pub account_owner: Option<String>,
     pub transaction_id: String,
     pub transaction_type: String,
 }
 
 #[derive(Deserialize)]
 pub struct Location {
-    pub address: String,
-    pub city: String,
-    pub lat: f64,
-    pub lon: f64,
-    pub region: String,
-    pub store_number: String,
-    pub postal_code: String,
-    pub country: String,
+    pub address: Option<String>,
+    pub city: Option<String>,
+    pub lat: Option<f64>,
+    pub lon: Option<f64>,
+    pub region: Option<String>,
+    pub store_number: Option<String>,
+    pub postal_code: Option<String>,
+    pub country: Option<String>,
 }
 
 #[derive(Deserialize)]
 pub struct PaymentMeta {
-    pub by_order_of: String,
-    pub payee: String,
-    pub payer: String,
-    pub payment_method: String,
-    pub payment_processor: String,
-    pub ppd_id: String,
-    pub reason: String,
-    pub reference_number: String,
+    pub by_order_of: Option<String>,
+    pub payee: Option<String>,
+    pub payer: Option<String>,
+    pub payment_method: Option<String>,
+    pub payment_processor: Option<String>,
+    pub ppd_id: Option<String>,
+    pub reason: Option<String>,
+    pub reference_number: Option<String>,
 }
 
-#[derive(Serialize)]
-struct GetTransactionsRequestOptions<'a> {
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct GetTransactionsRequestOptions<'a> {
     #[serde(skip_serializing_if = "Option::is_none")]
     account_ids: Option<Vec<&'a str>>,
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -73,10 +94,35 @@ struct GetTransactionsRequestOptions<'a> {
     offset: Option<i64>,
 }
 
+impl<'a> GetTransactionsRequestOptions<'a> {
+    pub fn new() -> GetTransactionsRequestOptions<'a> {
+        GetTransactionsRequestOptions::default()
+    }
+
+    /// Only returns data for the accounts with these IDs.
+    pub fn with_account_ids(
+        mut self,
+        account_ids: Vec<&'a str>,
+    ) -> GetTransactionsRequestOptions<'a> {
+        self.account_ids = Some(account_ids);
+        self
+    }
+
+    /// Caps how many transactions are returned, up to 500.
+    pub fn with_count(mut self, count: i64) -> GetTransactionsRequestOptions<'a> {
+        self.count = Some(count);
+        self
+    }
+
+    /// Skips this many transactions, for paging through results.
+    pub fn with_offset(mut self, offset: i64) -> GetTransactionsRequestOptions<'a> {
+        self.offset = Some(offset);
+        self
+    }
+}
+
 #[derive(Serialize)]
 struct GetTransactionsRequest<'a> {
-    client_id: &'a str,
-    secret: &'a str,
     access_token: &'a str,
     start_date: &'a str,
     end_date: &'a str,
@@ -84,6 +130,11 @@ struct GetTransactionsRequest<'a> {
     options: Option<GetTransactionsRequestOptions<'a>>,
 }
 
+impl Endpoint for GetTransactionsRequest<'_> {
+    type Response = GetTransactionsResponse;
+    const PATH: &'static str = "/transactions/get";
+}
+
 #[derive(Deserialize)]
 pub struct GetTransactionsResponse {
     pub request_id: String,
@@ -93,7 +144,69 @@ pub struct GetTransactionsResponse {
     pub total_transactions: i64,
 }
 
-impl<'a> Transactions for Client<'a> {
+#[derive(Serialize)]
+struct RefreshTransactionsRequest<'a> {
+    access_token: &'a str,
+}
+
+impl Endpoint for RefreshTransactionsRequest<'_> {
+    type Response = RefreshTransactionsResponse;
+    const PATH: &'static str = "/transactions/refresh";
+}
+
+#[derive(Deserialize)]
+pub struct RefreshTransactionsResponse {
+    pub request_id: String,
+}
+
+#[derive(Clone, Debug, Default, Serialize)]
+pub struct SyncTransactionsRequestOptions {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    count: Option<i64>,
+}
+
+impl SyncTransactionsRequestOptions {
+    pub fn new() -> SyncTransactionsRequestOptions {
+        SyncTransactionsRequestOptions::default()
+    }
+
+    /// Caps how many updates are returned per page, up to 500.
+    pub fn with_count(mut self, count: i64) -> SyncTransactionsRequestOptions {
+        self.count = Some(count);
+        self
+    }
+}
+
+#[derive(Serialize)]
+struct SyncTransactionsRequest<'a> {
+    access_token: &'a str,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    cursor: Option<&'a str>,
+    #[serde(flatten, skip_serializing_if = "Option::is_none")]
+    options: Option<SyncTransactionsRequestOptions>,
+}
+
+impl Endpoint for SyncTransactionsRequest<'_> {
+    type Response = SyncTransactionsResponse;
+    const PATH: &'static str = "/transactions/sync";
+}
+
+#[derive(Deserialize)]
+pub struct SyncTransactionsResponse {
+    pub request_id: String,
+    pub added: Vec<Transaction>,
+    pub modified: Vec<Transaction>,
+    pub removed: Vec<RemovedTransaction>,
+    pub next_cursor: String,
+    pub has_more: bool,
+}
+
+#[derive(Deserialize)]
+pub struct RemovedTransaction {
+    pub transaction_id: String,
+}
+
+impl Transactions for Client {
     fn get_transactions(
         &self,
         access_token: &str,
@@ -110,23 +223,856 @@ impl<'a> Transactions for Client<'a> {
         end_date: &str,
         options: Option<GetTransactionsRequestOptions>,
     ) -> Result<GetTransactionsResponse, Error> {
-        if start_date == "" || end_date == "" {
+        if start_date.is_empty() || end_date.is_empty() {
             Err(Error::new(Kind::ValidationError(
                 "start date and end date must be specified",
             )))?
         }
 
         let req = GetTransactionsRequest {
-            client_id: self.client_id,
-            secret: self.secret,
             access_token,
             start_date,
             end_date,
             options,
         };
 
-        serde_json::to_string(&req)
-            .map_err(|err| Error::new(Kind::Json(err)))
-            .and_then(|json_body| self.call("/transactions/get", &json_body))
+        self.send(&req)
+    }
+
+    fn refresh_transactions(
+        &self,
+        access_token: &str,
+    ) -> Result<RefreshTransactionsResponse, Error> {
+        if access_token.is_empty() {
+            Err(Error::new(Kind::EmptyToken))?
+        }
+
+        let req = RefreshTransactionsRequest { access_token };
+
+        self.send(&req)
+    }
+
+    fn sync_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+        options: Option<SyncTransactionsRequestOptions>,
+    ) -> Result<SyncTransactionsResponse, Error> {
+        if access_token.is_empty() {
+            Err(Error::new(Kind::EmptyToken))?
+        }
+
+        let req = SyncTransactionsRequest {
+            access_token,
+            cursor,
+            options,
+        };
+
+        self.send(&req)
+    }
+}
+
+#[cfg(feature = "async")]
+pub trait AsyncTransactions {
+    fn get_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> ResponseFuture<GetTransactionsResponse>;
+    fn get_transactions_with_options(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        options: Option<GetTransactionsRequestOptions>,
+    ) -> ResponseFuture<GetTransactionsResponse>;
+    fn refresh_transactions(
+        &self,
+        access_token: &str,
+    ) -> ResponseFuture<RefreshTransactionsResponse>;
+    fn sync_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+        options: Option<SyncTransactionsRequestOptions>,
+    ) -> ResponseFuture<SyncTransactionsResponse>;
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransactions for AsyncClient {
+    fn get_transactions(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> ResponseFuture<GetTransactionsResponse> {
+        self.get_transactions_with_options(access_token, start_date, end_date, None)
+    }
+
+    fn get_transactions_with_options(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        options: Option<GetTransactionsRequestOptions>,
+    ) -> ResponseFuture<GetTransactionsResponse> {
+        if start_date.is_empty() || end_date.is_empty() {
+            return fail(Kind::ValidationError(
+                "start date and end date must be specified",
+            ));
+        }
+
+        let req = GetTransactionsRequest {
+            access_token,
+            start_date,
+            end_date,
+            options,
+        };
+
+        self.send(&req)
+    }
+
+    fn refresh_transactions(
+        &self,
+        access_token: &str,
+    ) -> ResponseFuture<RefreshTransactionsResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = RefreshTransactionsRequest { access_token };
+
+        self.send(&req)
+    }
+
+    fn sync_transactions(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+        options: Option<SyncTransactionsRequestOptions>,
+    ) -> ResponseFuture<SyncTransactionsResponse> {
+        if access_token.is_empty() {
+            return fail(Kind::EmptyToken);
+        }
+
+        let req = SyncTransactionsRequest {
+            access_token,
+            cursor,
+            options,
+        };
+
+        self.send(&req)
+    }
+}
+
+/// TransactionsApi groups the transactions endpoints, e.g.
+/// `client.transactions().get(..)`.
+pub struct TransactionsApi<'a, C> {
+    pub(crate) client: &'a C,
+}
+
+impl TransactionsApi<'_, Client> {
+    pub fn get(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> Result<GetTransactionsResponse, Error> {
+        self.client
+            .get_transactions(access_token, start_date, end_date)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        options: Option<GetTransactionsRequestOptions>,
+    ) -> Result<GetTransactionsResponse, Error> {
+        self.client
+            .get_transactions_with_options(access_token, start_date, end_date, options)
+    }
+
+    pub fn refresh(&self, access_token: &str) -> Result<RefreshTransactionsResponse, Error> {
+        self.client.refresh_transactions(access_token)
+    }
+
+    pub fn sync(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+        options: Option<SyncTransactionsRequestOptions>,
+    ) -> Result<SyncTransactionsResponse, Error> {
+        self.client.sync_transactions(access_token, cursor, options)
+    }
+
+    /// Fetches every update since `cursor`, see `sync_all`.
+    pub fn sync_all(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+    ) -> Result<TransactionsUpdate, Error> {
+        sync_all(
+            self.client,
+            access_token,
+            cursor,
+            MAX_PAGE_SIZE,
+            &sync_restart_policy(),
+        )
+    }
+
+    /// Walks every transaction between `start_date` and `end_date`, see
+    /// `TransactionsIter`.
+    pub fn iter(&self, access_token: &str, start_date: &str, end_date: &str) -> TransactionsIter {
+        TransactionsIter::new(self.client, access_token, start_date, end_date)
+    }
+}
+
+#[cfg(feature = "async")]
+impl TransactionsApi<'_, AsyncClient> {
+    pub fn get(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> ResponseFuture<GetTransactionsResponse> {
+        self.client
+            .get_transactions(access_token, start_date, end_date)
+    }
+
+    pub fn get_with_options(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+        options: Option<GetTransactionsRequestOptions>,
+    ) -> ResponseFuture<GetTransactionsResponse> {
+        self.client
+            .get_transactions_with_options(access_token, start_date, end_date, options)
+    }
+
+    pub fn refresh(&self, access_token: &str) -> ResponseFuture<RefreshTransactionsResponse> {
+        self.client.refresh_transactions(access_token)
+    }
+
+    pub fn sync(
+        &self,
+        access_token: &str,
+        cursor: Option<&str>,
+        options: Option<SyncTransactionsRequestOptions>,
+    ) -> ResponseFuture<SyncTransactionsResponse> {
+        self.client.sync_transactions(access_token, cursor, options)
+    }
+
+    /// Streams every transaction between `start_date` and `end_date`, see
+    /// `TransactionsStream`.
+    pub fn stream(
+        &self,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> TransactionsStream {
+        TransactionsStream::new(self.client, access_token, start_date, end_date)
+    }
+}
+
+// TransactionPages is the state shared by `TransactionsIter` and
+// `TransactionsStream`.
+struct TransactionPages {
+    access_token: String,
+    start_date: String,
+    end_date: String,
+    account_ids: Option<Vec<String>>,
+    pager: Pager<Transaction>,
+    accounts: Vec<Account>,
+    item: Option<Item>,
+}
+
+impl TransactionPages {
+    fn new(access_token: &str, start_date: &str, end_date: &str) -> TransactionPages {
+        TransactionPages {
+            access_token: access_token.to_string(),
+            start_date: start_date.to_string(),
+            end_date: end_date.to_string(),
+            account_ids: None,
+            pager: Pager::new(),
+            accounts: Vec::new(),
+            item: None,
+        }
+    }
+
+    fn options(&self, offset: i64) -> GetTransactionsRequestOptions<'_> {
+        let mut options = GetTransactionsRequestOptions::new()
+            .with_count(self.pager.page_size)
+            .with_offset(offset);
+        if let Some(ref account_ids) = self.account_ids {
+            options = options.with_account_ids(account_ids.iter().map(String::as_str).collect());
+        }
+        options
+    }
+
+    fn receive(&mut self, resp: GetTransactionsResponse) {
+        if self.pager.is_first_page() {
+            self.accounts = resp.accounts;
+            self.item = Some(resp.item);
+        }
+        self.pager
+            .push_page(resp.total_transactions, resp.transactions, |transaction| {
+                &transaction.transaction_id
+            });
+    }
+}
+
+/// TransactionsIter walks every transaction in a date range, fetching pages
+/// of up to 500 transactions as it goes. Transactions that shift across a page
+/// boundary while the range is walked are only returned once.
+///
+/// The accounts and item are taken from the first page, and can be read once
+/// the first transaction has been returned. The walk stops after the first
+/// error.
+pub struct TransactionsIter {
+    client: Client,
+    pages: TransactionPages,
+}
+
+impl TransactionsIter {
+    pub fn new(
+        client: &Client,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> TransactionsIter {
+        TransactionsIter {
+            client: client.clone(),
+            pages: TransactionPages::new(access_token, start_date, end_date),
+        }
+    }
+
+    /// Sets how many transactions are fetched per call, up to 500.
+    pub fn with_page_size(mut self, page_size: i64) -> TransactionsIter {
+        self.pages.pager.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Only walks the transactions of the accounts with these IDs.
+    pub fn with_account_ids(mut self, account_ids: Vec<&str>) -> TransactionsIter {
+        self.pages.account_ids = Some(account_ids.into_iter().map(str::to_string).collect());
+        self
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.pages.accounts
+    }
+
+    pub fn item(&self) -> Option<&Item> {
+        self.pages.item.as_ref()
+    }
+
+    /// Returns the number of transactions in the range, as of the last page
+    /// fetched.
+    pub fn total_transactions(&self) -> Option<i64> {
+        self.pages.pager.total()
+    }
+}
+
+impl Iterator for TransactionsIter {
+    type Item = Result<Transaction, Error>;
+
+    fn next(&mut self) -> Option<Result<Transaction, Error>> {
+        loop {
+            if let Some(transaction) = self.pages.pager.pop() {
+                return Some(Ok(transaction));
+            }
+
+            let offset = self.pages.pager.next_offset()?;
+            let result = self.client.get_transactions_with_options(
+                &self.pages.access_token,
+                &self.pages.start_date,
+                &self.pages.end_date,
+                Some(self.pages.options(offset)),
+            );
+            match result {
+                Ok(resp) => self.pages.receive(resp),
+                Err(err) => {
+                    self.pages.pager.finish();
+                    return Some(Err(err));
+                }
+            }
+        }
+    }
+}
+
+/// TransactionsStream is the non-blocking counterpart of `TransactionsIter`.
+#[cfg(feature = "async")]
+pub struct TransactionsStream {
+    client: AsyncClient,
+    pages: TransactionPages,
+    pending: Option<ResponseFuture<GetTransactionsResponse>>,
+}
+
+#[cfg(feature = "async")]
+impl TransactionsStream {
+    pub fn new(
+        client: &AsyncClient,
+        access_token: &str,
+        start_date: &str,
+        end_date: &str,
+    ) -> TransactionsStream {
+        TransactionsStream {
+            client: client.clone(),
+            pages: TransactionPages::new(access_token, start_date, end_date),
+            pending: None,
+        }
+    }
+
+    /// Sets how many transactions are fetched per call, up to 500.
+    pub fn with_page_size(mut self, page_size: i64) -> TransactionsStream {
+        self.pages.pager.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Only streams the transactions of the accounts with these IDs.
+    pub fn with_account_ids(mut self, account_ids: Vec<&str>) -> TransactionsStream {
+        self.pages.account_ids = Some(account_ids.into_iter().map(str::to_string).collect());
+        self
+    }
+
+    pub fn accounts(&self) -> &[Account] {
+        &self.pages.accounts
+    }
+
+    pub fn item(&self) -> Option<&Item> {
+        self.pages.item.as_ref()
+    }
+
+    /// Returns the number of transactions in the range, as of the last page
+    /// fetched.
+    pub fn total_transactions(&self) -> Option<i64> {
+        self.pages.pager.total()
+    }
+}
+
+#[cfg(feature = "async")]
+impl Stream for TransactionsStream {
+    type Item = Transaction;
+    type Error = Error;
+
+    fn poll(&mut self) -> Poll<Option<Transaction>, Error> {
+        loop {
+            if let Some(transaction) = self.pages.pager.pop() {
+                return Ok(Async::Ready(Some(transaction)));
+            }
+
+            let polled = match self.pending {
+                Some(ref mut pending) => pending.poll(),
+                None => {
+                    let offset = match self.pages.pager.next_offset() {
+                        Some(offset) => offset,
+                        None => return Ok(Async::Ready(None)),
+                    };
+                    self.pending = Some(self.client.get_transactions_with_options(
+                        &self.pages.access_token,
+                        &self.pages.start_date,
+                        &self.pages.end_date,
+                        Some(self.pages.options(offset)),
+                    ));
+                    continue;
+                }
+            };
+            match polled {
+                Ok(Async::NotReady) => return Ok(Async::NotReady),
+                Ok(Async::Ready(resp)) => {
+                    self.pending = None;
+                    self.pages.receive(resp);
+                }
+                Err(err) => {
+                    self.pending = None;
+                    self.pages.pager.finish();
+                    return Err(err);
+                }
+            }
+        }
+    }
+}
+
+/// TransactionsUpdate is every change to an item's transactions between two
+/// sync cursors.
+#[derive(Default)]
+pub struct TransactionsUpdate {
+    pub added: Vec<Transaction>,
+    pub modified: Vec<Transaction>,
+    pub removed: Vec<RemovedTransaction>,
+    /// The cursor to sync from next time.
+    pub next_cursor: String,
+}
+
+/// Drains `/transactions/sync` from `cursor`, or from the start without one,
+/// until Plaid has no more updates. Plaid fails a page with
+/// `TRANSACTIONS_SYNC_MUTATION_DURING_PAGINATION` when the item changes while
+/// it is paged through; the whole sync is then started over from `cursor`,
+/// after backing off as `restart_policy` says. Once the policy's attempts are
+/// used up, the last such error is returned.
+///
+/// The facade's `sync_all` and `TransactionsSync` make up to four attempts,
+/// backing off from one second to half a minute.
+pub fn sync_all(
+    client: &Client,
+    access_token: &str,
+    cursor: Option<&str>,
+    page_size: i64,
+    restart_policy: &RetryPolicy,
+) -> Result<TransactionsUpdate, Error> {
+    let options = SyncTransactionsRequestOptions::new().with_count(page_size);
+
+    let mut attempt = 1;
+    'restart: loop {
+        let mut update = TransactionsUpdate::default();
+        let mut next_cursor = cursor.map(str::to_string);
+        loop {
+            let resp = match client.sync_transactions(
+                access_token,
+                next_cursor.as_deref(),
+                Some(options.clone()),
+            ) {
+                Err(ref err)
+                    if err.error_code()
+                        == Some(&ErrorCode::TransactionsSyncMutationDuringPagination)
+                        && attempt < restart_policy.max_attempts =>
+                {
+                    thread::sleep(restart_policy.backoff(attempt));
+                    attempt += 1;
+                    continue 'restart;
+                }
+                // Other errors already carry the client's own attempt count.
+                Err(err)
+                    if err.error_code()
+                        == Some(&ErrorCode::TransactionsSyncMutationDuringPagination)
+                        && attempt > 1 =>
+                {
+                    return Err(err.with_attempts(attempt));
+                }
+                resp => resp?,
+            };
+
+            update.added.extend(resp.added);
+            update.modified.extend(resp.modified);
+            update.removed.extend(resp.removed);
+            if !resp.has_more {
+                update.next_cursor = resp.next_cursor;
+                return Ok(update);
+            }
+            next_cursor = Some(resp.next_cursor);
+        }
+    }
+}
+
+fn sync_restart_policy() -> RetryPolicy {
+    RetryPolicy::default()
+        .with_max_attempts(4)
+        .with_backoff(Duration::from_secs(1), Duration::from_secs(30))
+}
+
+/// TransactionsSync keeps items' transactions up to date with
+/// `/transactions/sync`, resuming every item from the cursor saved in a
+/// `CursorStore`.
+///
+/// `sync` returns the changes since the saved cursor without saving the new
+/// one; call `commit` once the changes are applied, so a worker that stops
+/// in between syncs the same changes again rather than losing them.
+#[derive(Clone)]
+pub struct TransactionsSync {
+    client: Client,
+    store: Arc<dyn CursorStore>,
+    page_size: i64,
+    restart_policy: RetryPolicy,
+}
+
+impl TransactionsSync {
+    pub fn new<S: CursorStore + 'static>(client: Client, store: S) -> TransactionsSync {
+        TransactionsSync {
+            client,
+            store: Arc::new(store),
+            page_size: MAX_PAGE_SIZE,
+            restart_policy: sync_restart_policy(),
+        }
+    }
+
+    /// Sets how many updates are fetched per call, up to 500.
+    pub fn with_page_size(mut self, page_size: i64) -> TransactionsSync {
+        self.page_size = page_size.clamp(1, MAX_PAGE_SIZE);
+        self
+    }
+
+    /// Sets how often and how long a sync backs off when Plaid reports that
+    /// the item changed while it was paged through, see `sync_all`.
+    pub fn with_restart_policy(mut self, policy: RetryPolicy) -> TransactionsSync {
+        self.restart_policy = policy;
+        self
+    }
+
+    /// Fetches every change to the item's transactions since its saved
+    /// cursor.
+    pub fn sync(&self, item_id: &str, access_token: &str) -> Result<TransactionsUpdate, Error> {
+        let cursor = self.store.load(item_id)?;
+        sync_all(
+            &self.client,
+            access_token,
+            cursor.as_deref(),
+            self.page_size,
+            &self.restart_policy,
+        )
+    }
+
+    /// Saves the cursor of an applied update, so the next sync starts after
+    /// it.
+    pub fn commit(&self, item_id: &str, update: &TransactionsUpdate) -> Result<(), Error> {
+        self.store.save(item_id, &update.next_cursor)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::cursors::InMemoryCursorStore;
+    use crate::environments::Environment;
+    use crate::mock::MockPlaid;
+    use crate::transport::InMemoryTransport;
+    use serde_json::Value;
+
+    const ACCESS_TOKEN: &str = "access-sandbox-de3ce8ef-33f8-452c-a685-8671031fc0f6";
+
+    const MUTATION_DURING_PAGINATION: &str = r#"{
+        "display_message": null,
+        "error_code": "TRANSACTIONS_SYNC_MUTATION_DURING_PAGINATION",
+        "error_message": "underlying transaction data changed since last page was fetched",
+        "error_type": "TRANSACTIONS_ERROR",
+        "request_id": "6bYHd8UcCzWYSdq"
+    }"#;
+
+    fn restart_now() -> RetryPolicy {
+        sync_restart_policy().with_backoff(Duration::from_millis(0), Duration::from_millis(0))
+    }
+
+    // page returns the fixture response cut down to the transactions in
+    // `range`, reporting `total` transactions.
+    fn page(range: std::ops::Range<usize>, total: usize) -> String {
+        let mut resp: Value =
+            serde_json::from_str(include_str!("../fixtures/transactions/get.json")).unwrap();
+        let transactions = resp["transactions"].as_array().unwrap()[range].to_vec();
+        resp["transactions"] = Value::Array(transactions);
+        resp["total_transactions"] = total.into();
+        resp.to_string()
+    }
+
+    #[test]
+    fn test_iter() {
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(MockPlaid::new());
+
+        let mut transactions = test_client
+            .transactions()
+            .iter(ACCESS_TOKEN, "2019-08-01", "2019-08-31")
+            .with_page_size(5);
+        let fetched: Vec<Transaction> = transactions
+            .by_ref()
+            .map(|transaction| transaction.unwrap())
+            .collect();
+
+        assert_eq!(12, fetched.len());
+        assert_eq!(None, fetched[0].location.city);
+        assert_eq!(None, fetched[0].pending_transaction_id);
+        assert_eq!(Some("Chicago"), fetched[1].location.city.as_deref());
+        assert_eq!(Some(41.8847), fetched[1].location.lat);
+        assert_eq!(Some(12), transactions.total_transactions());
+        assert_eq!(8, transactions.accounts().len());
+        assert!(transactions.item().is_some());
+    }
+
+    #[test]
+    fn test_iter_dedupes() {
+        let transport = InMemoryTransport::new()
+            .with_response("/transactions/get", &page(0..2, 3))
+            .with_response("/transactions/get", &page(1..3, 3));
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone());
+
+        let transactions: Vec<Transaction> =
+            TransactionsIter::new(&test_client, ACCESS_TOKEN, "2019-08-01", "2019-08-31")
+                .with_page_size(2)
+                .collect::<Result<_, _>>()
+                .unwrap();
+
+        assert_eq!(3, transactions.len());
+        let requests = transport.requests();
+        assert_eq!(2, requests.len());
+        assert!(requests[1].body.contains(r#""offset":2"#));
+    }
+
+    #[test]
+    fn test_iter_stops_on_error() {
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(MockPlaid::new());
+
+        let mut transactions =
+            test_client
+                .transactions()
+                .iter("access-sandbox-unknown", "2019-08-01", "2019-08-31");
+
+        assert!(transactions.next().unwrap().is_err());
+        assert!(transactions.next().is_none());
+    }
+
+    #[cfg(feature = "async")]
+    #[test]
+    fn test_stream() {
+        let test_client =
+            AsyncClient::new("client_id", "secret", "public_key", Environment::SANDBOX)
+                .with_transport(MockPlaid::new());
+
+        let stream = test_client
+            .transactions()
+            .stream(ACCESS_TOKEN, "2019-08-01", "2019-08-31")
+            .with_page_size(5);
+        let mut runtime = tokio::runtime::Runtime::new().unwrap();
+        let transactions = runtime.block_on(stream.collect()).unwrap();
+
+        assert_eq!(12, transactions.len());
+    }
+
+    // sync_page returns a sync response adding the fixture transactions in
+    // `range`.
+    fn sync_page(range: std::ops::Range<usize>, next_cursor: &str, has_more: bool) -> String {
+        let fixture: Value =
+            serde_json::from_str(include_str!("../fixtures/transactions/get.json")).unwrap();
+        serde_json::json!({
+            "added": fixture["transactions"].as_array().unwrap()[range].to_vec(),
+            "modified": [],
+            "removed": [],
+            "next_cursor": next_cursor,
+            "has_more": has_more,
+            "request_id": "Wvhy9PZHQLV8njG",
+        })
+        .to_string()
+    }
+
+    #[test]
+    fn test_sync_all() {
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(MockPlaid::new());
+
+        let update = sync_all(&test_client, ACCESS_TOKEN, None, 5, &sync_restart_policy()).unwrap();
+        assert_eq!(12, update.added.len());
+
+        let update = test_client
+            .transactions()
+            .sync_all(ACCESS_TOKEN, Some(&update.next_cursor))
+            .unwrap();
+        assert!(update.added.is_empty());
+    }
+
+    #[test]
+    fn test_sync_all_restarts_on_mutation() {
+        let transport = InMemoryTransport::new()
+            .with_response("/transactions/sync", &sync_page(0..1, "cursor-a", true))
+            .with_status_response("/transactions/sync", 400, MUTATION_DURING_PAGINATION)
+            .with_response("/transactions/sync", &sync_page(0..2, "cursor-b", false));
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone());
+
+        let update = sync_all(
+            &test_client,
+            ACCESS_TOKEN,
+            Some("cursor-0"),
+            1,
+            &restart_now(),
+        )
+        .unwrap();
+
+        assert_eq!(2, update.added.len());
+        assert_eq!("cursor-b", update.next_cursor);
+        let cursors: Vec<Value> = transport
+            .requests_to("/transactions/sync")
+            .iter()
+            .map(|request| serde_json::from_str::<Value>(&request.body).unwrap()["cursor"].clone())
+            .collect();
+        assert_eq!(vec!["cursor-0", "cursor-a", "cursor-0"], cursors);
+    }
+
+    #[test]
+    fn test_sync_all_gives_up_restarting() {
+        let transport = InMemoryTransport::new().with_status_response(
+            "/transactions/sync",
+            400,
+            MUTATION_DURING_PAGINATION,
+        );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone());
+
+        let err = match sync_all(
+            &test_client,
+            ACCESS_TOKEN,
+            None,
+            1,
+            &restart_now().with_max_attempts(3),
+        ) {
+            Err(err) => err,
+            Ok(_) => panic!("the item never stopped changing"),
+        };
+
+        assert_eq!(
+            Some(&ErrorCode::TransactionsSyncMutationDuringPagination),
+            err.error_code()
+        );
+        assert_eq!(3, err.attempts());
+        assert_eq!(3, transport.requests_to("/transactions/sync").len());
+    }
+
+    #[test]
+    fn test_sync_all_keeps_client_attempts() {
+        let transport = InMemoryTransport::new().with_status_response(
+            "/transactions/sync",
+            500,
+            r#"{
+                "display_message": null,
+                "error_code": "INTERNAL_SERVER_ERROR",
+                "error_message": "an unexpected error occurred",
+                "error_type": "API_ERROR",
+                "request_id": "Bxj2vM3E5gaj8iB"
+            }"#,
+        );
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(transport.clone())
+            .with_retry_policy(
+                RetryPolicy::default()
+                    .with_max_attempts(3)
+                    .with_backoff(Duration::from_millis(0), Duration::from_millis(0)),
+            );
+
+        let err = match sync_all(&test_client, ACCESS_TOKEN, None, 1, &restart_now()) {
+            Err(err) => err,
+            Ok(_) => panic!("the server never recovered"),
+        };
+
+        assert_eq!(Some(&ErrorCode::InternalServerError), err.error_code());
+        assert_eq!(3, err.attempts());
+        assert_eq!(3, transport.requests_to("/transactions/sync").len());
+    }
+
+    #[test]
+    fn test_transactions_sync() {
+        let store = InMemoryCursorStore::new();
+        let test_client = Client::new("client_id", "secret", "public_key", Environment::SANDBOX)
+            .with_transport(MockPlaid::new());
+        let transactions_sync = TransactionsSync::new(test_client, store.clone());
+
+        let update = transactions_sync.sync("item-1", ACCESS_TOKEN).unwrap();
+        assert_eq!(12, update.added.len());
+        assert_eq!(None, store.load("item-1").unwrap());
+
+        transactions_sync.commit("item-1", &update).unwrap();
+        assert_eq!(Some("cursor-12".to_string()), store.load("item-1").unwrap());
+        let update = transactions_sync.sync("item-1", ACCESS_TOKEN).unwrap();
+        assert!(update.added.is_empty());
     }
 }
@@ -0,0 +1,432 @@
+use crate::errors::{Error, Kind};
+#[cfg(feature = "async")]
+use futures::{future, Future, Stream};
+use std::collections::HashMap;
+use std::io::{self, Read};
+use std::sync::{Arc, Mutex};
+use std::time::Duration;
+
+/// HttpRequest is a fully prepared request to the Plaid API. Every Plaid
+/// endpoint is a JSON `POST`, so only the target, headers and body vary.
+///
+/// Blocking transports should give up on the request once `timeout` has
+/// passed; `AsyncClient` enforces it itself.
+#[derive(Clone, Debug)]
+pub struct HttpRequest {
+    pub url: String,
+    pub endpoint: String,
+    pub headers: Vec<(String, String)>,
+    pub body: String,
+    pub timeout: Option<Duration>,
+}
+
+/// HttpResponse is the raw response returned by a transport, before any
+/// decoding has happened.
+#[derive(Clone, Debug)]
+pub struct HttpResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Vec<u8>,
+}
+
+impl HttpResponse {
+    /// Builds a response carrying a JSON body.
+    pub fn json(status: u16, body: &str) -> HttpResponse {
+        HttpResponse {
+            status,
+            headers: vec![("Content-Type".to_string(), "application/json".to_string())],
+            body: body.as_bytes().to_vec(),
+        }
+    }
+
+    pub fn text(&self) -> String {
+        String::from_utf8_lossy(&self.body).into_owned()
+    }
+}
+
+/// StreamedResponse is a response whose body is read as it arrives, for
+/// bodies too large to hold in memory, such as asset report PDFs.
+pub struct StreamedResponse {
+    pub status: u16,
+    pub headers: Vec<(String, String)>,
+    pub body: Box<dyn Read + Send>,
+}
+
+impl From<HttpResponse> for StreamedResponse {
+    fn from(response: HttpResponse) -> StreamedResponse {
+        StreamedResponse {
+            status: response.status,
+            headers: response.headers,
+            body: Box::new(io::Cursor::new(response.body)),
+        }
+    }
+}
+
+/// Transport sends prepared requests on behalf of `Client`. Implement it to
+/// route calls somewhere other than the Plaid servers.
+pub trait Transport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error>;
+
+    /// Sends `request` like `send`, but returns as soon as the response
+    /// headers are in and leaves the body to be read. By default the body is
+    /// read in full by `send`.
+    fn send_streamed(&self, request: HttpRequest) -> Result<StreamedResponse, Error> {
+        self.send(request).map(StreamedResponse::from)
+    }
+}
+
+/// The boxed future returned by `AsyncTransport::send`.
+#[cfg(feature = "async")]
+pub type TransportFuture = Box<dyn Future<Item = HttpResponse, Error = Error> + Send>;
+
+/// AsyncTransport is the non-blocking counterpart of `Transport` used by
+/// `AsyncClient`.
+#[cfg(feature = "async")]
+pub trait AsyncTransport: Send + Sync {
+    fn send(&self, request: HttpRequest) -> TransportFuture;
+}
+
+/// ReqwestTransport sends requests over the network with a blocking
+/// `reqwest::Client`. It is the default transport of `Client`.
+///
+/// Blocking reqwest clients only have a timeout for all their requests, so
+/// the transport keeps a client for each of the last few request timeouts it
+/// has seen, dropping the least recently used one beyond that. A transport
+/// made from a client of your own sends everything through that client and
+/// leaves timeouts to it.
+pub struct ReqwestTransport {
+    http_client: reqwest::Client,
+    connect_timeout: Option<Duration>,
+    timed_clients: Option<Mutex<Vec<(Duration, reqwest::Client)>>>,
+}
+
+// How many clients with distinct timeouts a ReqwestTransport keeps. This
+// covers the default timeouts and a few overrides; calls with many different
+// timeouts build clients more often instead of growing without bound.
+const MAX_TIMED_CLIENTS: usize = 8;
+
+impl ReqwestTransport {
+    pub fn new() -> ReqwestTransport {
+        ReqwestTransport {
+            http_client: reqwest::Client::new(),
+            connect_timeout: None,
+            timed_clients: Some(Mutex::new(Vec::new())),
+        }
+    }
+
+    /// Bounds how long connecting to Plaid may take.
+    pub fn with_connect_timeout(mut self, connect_timeout: Duration) -> ReqwestTransport {
+        self.connect_timeout = Some(connect_timeout);
+        self
+    }
+
+    fn client_for(&self, timeout: Option<Duration>) -> Result<reqwest::Client, Error> {
+        let (timed_clients, timeout) = match (&self.timed_clients, timeout) {
+            (Some(timed_clients), Some(timeout)) => (timed_clients, timeout),
+            _ => return Ok(self.http_client.clone()),
+        };
+
+        // Clients are kept most recently used first.
+        let mut timed_clients = timed_clients.lock().unwrap();
+        if let Some(i) = timed_clients.iter().position(|&(t, _)| t == timeout) {
+            let entry = timed_clients.remove(i);
+            let http_client = entry.1.clone();
+            timed_clients.insert(0, entry);
+            return Ok(http_client);
+        }
+
+        let mut builder = reqwest::Client::builder().timeout(timeout);
+        if let Some(connect_timeout) = self.connect_timeout {
+            builder = builder.connect_timeout(connect_timeout);
+        }
+        let http_client = builder
+            .build()
+            .map_err(|err| Error::new(Kind::Reqwest(err)))?;
+        timed_clients.insert(0, (timeout, http_client.clone()));
+        timed_clients.truncate(MAX_TIMED_CLIENTS);
+        Ok(http_client)
+    }
+
+    // post sends `request` and returns once the response headers are in.
+    fn post(&self, request: HttpRequest) -> Result<reqwest::Response, Error> {
+        let timeout = request.timeout;
+        let mut builder = self.client_for(timeout)?.post(request.url.as_str());
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        builder
+            .body(request.body)
+            .send()
+            .map_err(|err| reqwest_error(err, timeout))
+    }
+}
+
+impl Default for ReqwestTransport {
+    fn default() -> ReqwestTransport {
+        ReqwestTransport::new()
+    }
+}
+
+impl From<reqwest::Client> for ReqwestTransport {
+    fn from(http_client: reqwest::Client) -> ReqwestTransport {
+        ReqwestTransport {
+            http_client,
+            connect_timeout: None,
+            timed_clients: None,
+        }
+    }
+}
+
+impl Transport for ReqwestTransport {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        let timeout = request.timeout;
+        let mut res = self.post(request)?;
+
+        let mut body = Vec::new();
+        res.copy_to(&mut body)
+            .map_err(|err| reqwest_error(err, timeout))?;
+
+        Ok(HttpResponse {
+            status: res.status().as_u16(),
+            headers: collect_headers(res.headers()),
+            body,
+        })
+    }
+
+    fn send_streamed(&self, request: HttpRequest) -> Result<StreamedResponse, Error> {
+        let res = self.post(request)?;
+
+        Ok(StreamedResponse {
+            status: res.status().as_u16(),
+            headers: collect_headers(res.headers()),
+            body: Box::new(res),
+        })
+    }
+}
+
+// reqwest_error reports reqwest timeouts as timeouts of the request.
+fn reqwest_error(err: reqwest::Error, timeout: Option<Duration>) -> Error {
+    match timeout {
+        Some(timeout) if err.is_timeout() => Error::new(Kind::Timeout(timeout)),
+        _ => Error::new(Kind::Reqwest(err)),
+    }
+}
+
+/// AsyncReqwestTransport sends requests over the network with
+/// `reqwest::async::Client`. It is the default transport of `AsyncClient`.
+#[cfg(feature = "async")]
+pub struct AsyncReqwestTransport {
+    http_client: reqwest::r#async::Client,
+}
+
+#[cfg(feature = "async")]
+impl AsyncReqwestTransport {
+    pub fn new() -> AsyncReqwestTransport {
+        AsyncReqwestTransport::from(reqwest::r#async::Client::new())
+    }
+}
+
+#[cfg(feature = "async")]
+impl Default for AsyncReqwestTransport {
+    fn default() -> AsyncReqwestTransport {
+        AsyncReqwestTransport::new()
+    }
+}
+
+#[cfg(feature = "async")]
+impl From<reqwest::r#async::Client> for AsyncReqwestTransport {
+    fn from(http_client: reqwest::r#async::Client) -> AsyncReqwestTransport {
+        AsyncReqwestTransport { http_client }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for AsyncReqwestTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture {
+        let mut builder = self.http_client.post(request.url.as_str());
+        for (name, value) in &request.headers {
+            builder = builder.header(name.as_str(), value.as_str());
+        }
+
+        Box::new(
+            builder
+                .body(request.body)
+                .send()
+                .and_then(|res| {
+                    let status = res.status().as_u16();
+                    let headers = collect_headers(res.headers());
+                    res.into_body().concat2().map(move |body| HttpResponse {
+                        status,
+                        headers,
+                        body: body.to_vec(),
+                    })
+                })
+                .map_err(|err| Error::new(Kind::Reqwest(err))),
+        )
+    }
+}
+
+fn collect_headers(headers: &reqwest::header::HeaderMap) -> Vec<(String, String)> {
+    headers
+        .iter()
+        .filter_map(|(name, value)| {
+            value
+                .to_str()
+                .ok()
+                .map(|value| (name.as_str().to_string(), value.to_string()))
+        })
+        .collect()
+}
+
+/// InMemoryTransport answers requests with canned responses keyed by endpoint
+/// path, without touching the network. Responses registered for the same
+/// endpoint are served in order and the last one is repeated. Every request
+/// it receives is recorded so tests can inspect what was sent.
+///
+/// Clones share their responses and recorded requests, so a clone can be
+/// kept around after handing the transport to a client.
+#[derive(Clone, Default)]
+pub struct InMemoryTransport {
+    state: Arc<Mutex<InMemoryState>>,
+}
+
+#[derive(Default)]
+struct InMemoryState {
+    responses: HashMap<String, Vec<HttpResponse>>,
+    requests: Vec<HttpRequest>,
+}
+
+impl InMemoryTransport {
+    pub fn new() -> InMemoryTransport {
+        InMemoryTransport::default()
+    }
+
+    /// Queues a `200 OK` response with the given JSON body for `endpoint`.
+    pub fn with_response(self, endpoint: &str, body: &str) -> InMemoryTransport {
+        self.with_status_response(endpoint, 200, body)
+    }
+
+    /// Queues a response with the given status and JSON body for `endpoint`.
+    pub fn with_status_response(
+        self,
+        endpoint: &str,
+        status: u16,
+        body: &str,
+    ) -> InMemoryTransport {
+        self.push_response(endpoint, HttpResponse::json(status, body));
+        self
+    }
+
+    pub fn push_response(&self, endpoint: &str, response: HttpResponse) {
+        let mut state = self.state.lock().unwrap();
+        state
+            .responses
+            .entry(normalize_endpoint(endpoint))
+            .or_default()
+            .push(response);
+    }
+
+    /// Returns every request received so far, oldest first.
+    pub fn requests(&self) -> Vec<HttpRequest> {
+        self.state.lock().unwrap().requests.clone()
+    }
+
+    /// Returns the requests received for `endpoint`, oldest first.
+    pub fn requests_to(&self, endpoint: &str) -> Vec<HttpRequest> {
+        let endpoint = normalize_endpoint(endpoint);
+        self.requests()
+            .into_iter()
+            .filter(|req| req.endpoint == endpoint)
+            .collect()
+    }
+}
+
+impl Transport for InMemoryTransport {
+    fn send(&self, request: HttpRequest) -> Result<HttpResponse, Error> {
+        let mut state = self.state.lock().unwrap();
+        let endpoint = request.endpoint.clone();
+        state.requests.push(request);
+
+        match state.responses.get_mut(&endpoint) {
+            Some(queue) if queue.len() > 1 => Ok(queue.remove(0)),
+            Some(queue) if !queue.is_empty() => Ok(queue[0].clone()),
+            _ => Err(Error::new(Kind::Transport(format!(
+                "no response registered for {}",
+                endpoint
+            )))),
+        }
+    }
+}
+
+#[cfg(feature = "async")]
+impl AsyncTransport for InMemoryTransport {
+    fn send(&self, request: HttpRequest) -> TransportFuture {
+        Box::new(future::result(Transport::send(self, request)))
+    }
+}
+
+pub(crate) fn normalize_endpoint(endpoint: &str) -> String {
+    let mut path = endpoint.to_string();
+
+    if !endpoint.starts_with('/') {
+        path.insert(0, '/');
+    }
+
+    path
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn request(endpoint: &str) -> HttpRequest {
+        HttpRequest {
+            url: format!("https://sandbox.plaid.com{}", endpoint),
+            endpoint: endpoint.to_string(),
+            headers: vec![],
+            body: "{}".to_string(),
+            timeout: None,
+        }
+    }
+
+    #[test]
+    fn test_reqwest_transport_bounds_timed_clients() {
+        let transport = ReqwestTransport::new();
+
+        for secs in 1..=20 {
+            transport
+                .client_for(Some(Duration::from_secs(secs)))
+                .unwrap();
+        }
+        transport.client_for(Some(Duration::from_secs(15))).unwrap();
+
+        let timed_clients = transport.timed_clients.as_ref().unwrap().lock().unwrap();
+        let timeouts: Vec<u64> = timed_clients.iter().map(|&(t, _)| t.as_secs()).collect();
+        assert_eq!(vec![15, 20, 19, 18, 17, 16, 14, 13], timeouts);
+    }
+
+    #[test]
+    fn test_in_memory_transport_serves_responses_in_order() {
+        let transport = InMemoryTransport::new()
+            .with_response("/item/get", r#"{"n":1}"#)
+            .with_response("item/get", r#"{"n":2}"#);
+
+        let first = Transport::send(&transport, request("/item/get")).unwrap();
+        let second = Transport::send(&transport, request("/item/get")).unwrap();
+        let third = Transport::send(&transport, request("/item/get")).unwrap();
+
+        assert_eq!(r#"{"n":1}"#, first.text());
+        assert_eq!(r#"{"n":2}"#, second.text());
+        assert_eq!(r#"{"n":2}"#, third.text());
+        assert_eq!(3, transport.requests_to("/item/get").len());
+    }
+
+    #[test]
+    fn test_in_memory_transport_unknown_endpoint() {
+        let transport = InMemoryTransport::new();
+
+        assert!(Transport::send(&transport, request("/item/get")).is_err());
+        assert_eq!(1, transport.requests().len());
+    }
+}